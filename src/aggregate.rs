@@ -0,0 +1,478 @@
+// 区间聚合：线段树（带懒标记）与树状数组，运算由使用者通过 Monoid 指定
+use std::fmt::Debug;
+use std::ops::{Bound, RangeBounds};
+
+// 满足结合律且有单位元的运算
+pub trait Monoid {
+    type Item: Clone + Debug;
+
+    fn identity() -> Self::Item;
+    fn combine(a: &Self::Item, b: &Self::Item) -> Self::Item;
+
+    // x 自身结合 n 次，快速幂
+    fn pow(x: &Self::Item, mut n: usize) -> Self::Item {
+        let mut r = Self::identity();
+        let mut base = x.clone();
+        while n > 0 {
+            if n & 1 == 1 {
+                r = Self::combine(&r, &base);
+            }
+            base = Self::combine(&base, &base);
+            n >>= 1;
+        }
+        r
+    }
+}
+
+// 每个元素都有逆元的 Monoid，树状数组借此做区间查询
+pub trait Group: Monoid {
+    fn inverse(a: &Self::Item) -> Self::Item;
+}
+
+// 空运算，不需要聚合时使用
+impl Monoid for () {
+    type Item = ();
+
+    fn identity() {}
+    fn combine(_: &(), _: &()) {}
+}
+
+#[derive(Clone, Debug)]
+pub struct Sum;
+
+impl Monoid for Sum {
+    type Item = i64;
+
+    fn identity() -> i64 { 0 }
+    fn combine(a: &i64, b: &i64) -> i64 { a + b }
+    fn pow(x: &i64, n: usize) -> i64 { x * n as i64 }
+}
+
+impl Group for Sum {
+    fn inverse(a: &i64) -> i64 { -a }
+}
+
+#[derive(Clone, Debug)]
+pub struct Min;
+
+impl Monoid for Min {
+    type Item = i64;
+
+    fn identity() -> i64 { i64::MAX }
+    fn combine(a: &i64, b: &i64) -> i64 { *a.min(b) }
+}
+
+#[derive(Clone, Debug)]
+pub struct Max;
+
+impl Monoid for Max {
+    type Item = i64;
+
+    fn identity() -> i64 { i64::MIN }
+    fn combine(a: &i64, b: &i64) -> i64 { *a.max(b) }
+}
+
+#[derive(Clone, Debug)]
+pub struct Gcd;
+
+impl Monoid for Gcd {
+    type Item = u64;
+
+    fn identity() -> u64 { 0 }
+    fn combine(a: &u64, b: &u64) -> u64 {
+        let (mut a, mut b) = (*a, *b);
+        while b != 0 {
+            let t = a % b;
+            a = b;
+            b = t;
+        }
+        a
+    }
+}
+
+// 计数
+#[derive(Clone, Debug)]
+pub struct Count;
+
+impl Monoid for Count {
+    type Item = usize;
+
+    fn identity() -> usize { 0 }
+    fn combine(a: &usize, b: &usize) -> usize { a + b }
+    fn pow(x: &usize, n: usize) -> usize { x * n }
+}
+
+// 把 RangeBounds 转成左闭右开的 [l, r)
+fn _bounds<R: RangeBounds<usize>>(range: &R, len: usize) -> (usize, usize) {
+    let l = match range.start_bound() {
+        Bound::Included(&l) => l,
+        Bound::Excluded(&l) => l + 1,
+        Bound::Unbounded => 0,
+    };
+    let r = match range.end_bound() {
+        Bound::Included(&r) => r + 1,
+        Bound::Excluded(&r) => r,
+        Bound::Unbounded => len,
+    };
+    assert!(l <= r && r <= len, "range=[{}, {}) out of bounds, len={}", l, r, len);
+    (l, r)
+}
+
+// 线段树：区间修改 range_apply 把区间内每个元素 a 变为 combine(a, x)，
+// 要求运算满足交换律（Sum 即区间加，Min/Max 即区间取 min/max）
+pub struct SegmentTree<M: Monoid> {
+    n: usize,
+    tree: Vec<M::Item>,
+    lazy: Vec<Option<M::Item>>,
+}
+
+impl<M: Monoid> SegmentTree<M> {
+    pub fn new(n: usize) -> Self {
+        Self::from_vec(vec![M::identity(); n])
+    }
+
+    pub fn from_vec(vals: Vec<M::Item>) -> Self {
+        let n = vals.len();
+        let size = 4 * n.max(1);
+        let mut st = Self {
+            n,
+            tree: vec![M::identity(); size],
+            lazy: vec![None; size],
+        };
+        if n > 0 {
+            st._build(1, 0, n, &vals);
+        }
+        st
+    }
+
+    pub fn len(&self) -> usize { self.n }
+
+    pub fn is_empty(&self) -> bool { self.n == 0 }
+
+    pub fn get(&self, i: usize) -> M::Item { self.query(i..=i) }
+
+    // 单点赋值
+    pub fn set(&mut self, i: usize, x: M::Item) {
+        assert!(i < self.n, "index={} out of bounds, len={}", i, self.n);
+        self._set(1, 0, self.n, i, x);
+    }
+
+    // 单点修改：a[i] = combine(a[i], x)
+    pub fn apply(&mut self, i: usize, x: M::Item) { self.range_apply(i..=i, x) }
+
+    pub fn range_apply<R: RangeBounds<usize>>(&mut self, range: R, x: M::Item) {
+        let (l, r) = _bounds(&range, self.n);
+        if l < r {
+            self._range_apply(1, 0, self.n, l, r, &x);
+        }
+    }
+
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> M::Item {
+        let (l, r) = _bounds(&range, self.n);
+        if l == r {
+            return M::identity();
+        }
+        self._query(1, 0, self.n, l, r, &M::identity())
+    }
+
+    fn _build(&mut self, node: usize, l: usize, r: usize, vals: &[M::Item]) {
+        if r - l == 1 {
+            self.tree[node] = vals[l].clone();
+            return;
+        }
+        let mid = (l + r) / 2;
+        self._build(2 * node, l, mid, vals);
+        self._build(2 * node + 1, mid, r, vals);
+        self._pull(node);
+    }
+
+    fn _pull(&mut self, node: usize) {
+        self.tree[node] = M::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    // 给覆盖 len 个元素的节点打上标记
+    fn _apply_node(&mut self, node: usize, len: usize, x: &M::Item) {
+        self.tree[node] = M::combine(&self.tree[node], &M::pow(x, len));
+        self.lazy[node] = Some(match self.lazy[node].take() {
+            None => x.clone(),
+            Some(y) => M::combine(&y, x),
+        });
+    }
+
+    // 把标记下推给两个孩子
+    fn _push(&mut self, node: usize, l: usize, r: usize) {
+        if let Some(x) = self.lazy[node].take() {
+            let mid = (l + r) / 2;
+            self._apply_node(2 * node, mid - l, &x);
+            self._apply_node(2 * node + 1, r - mid, &x);
+        }
+    }
+
+    fn _set(&mut self, node: usize, l: usize, r: usize, i: usize, x: M::Item) {
+        if r - l == 1 {
+            self.tree[node] = x;
+            return;
+        }
+        self._push(node, l, r);
+        let mid = (l + r) / 2;
+        if i < mid {
+            self._set(2 * node, l, mid, i, x);
+        } else {
+            self._set(2 * node + 1, mid, r, i, x);
+        }
+        self._pull(node);
+    }
+
+    fn _range_apply(&mut self, node: usize, l: usize, r: usize, ql: usize, qr: usize, x: &M::Item) {
+        if qr <= l || r <= ql {
+            return;
+        }
+        if ql <= l && r <= qr {
+            self._apply_node(node, r - l, x);
+            return;
+        }
+        self._push(node, l, r);
+        let mid = (l + r) / 2;
+        self._range_apply(2 * node, l, mid, ql, qr, x);
+        self._range_apply(2 * node + 1, mid, r, ql, qr, x);
+        self._pull(node);
+    }
+
+    // 查询不下推标记，pending 为祖先上尚未下推的标记之和，在完全覆盖的节点上一并计入
+    fn _query(&self, node: usize, l: usize, r: usize, ql: usize, qr: usize, pending: &M::Item) -> M::Item {
+        if qr <= l || r <= ql {
+            return M::identity();
+        }
+        if ql <= l && r <= qr {
+            return M::combine(&self.tree[node], &M::pow(pending, r - l));
+        }
+        let pending = match self.lazy[node] {
+            None => pending.clone(),
+            Some(ref x) => M::combine(pending, x),
+        };
+        let mid = (l + r) / 2;
+        let left = self._query(2 * node, l, mid, ql, qr, &pending);
+        let right = self._query(2 * node + 1, mid, r, ql, qr, &pending);
+        M::combine(&left, &right)
+    }
+}
+
+// 树状数组：单点修改 a[i] = combine(a[i], x) 与前缀查询，要求运算满足交换律；
+// 运算是 Group 时还支持任意区间查询和单点赋值
+pub struct FenwickTree<M: Monoid> {
+    tree: Vec<M::Item>,
+}
+
+impl<M: Monoid> FenwickTree<M> {
+    pub fn new(n: usize) -> Self {
+        Self { tree: vec![M::identity(); n + 1] }
+    }
+
+    pub fn from_vec(vals: Vec<M::Item>) -> Self {
+        let n = vals.len();
+        let mut tree = Vec::with_capacity(n + 1);
+        tree.push(M::identity());
+        tree.extend(vals);
+        // 线性建树：每个节点把自己累加到父节点上
+        for i in 1..=n {
+            let p = i + (i & i.wrapping_neg());
+            if p <= n {
+                tree[p] = M::combine(&tree[p], &tree[i]);
+            }
+        }
+        Self { tree }
+    }
+
+    pub fn len(&self) -> usize { self.tree.len() - 1 }
+
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    pub fn apply(&mut self, i: usize, x: M::Item) {
+        assert!(i < self.len(), "index={} out of bounds, len={}", i, self.len());
+        let mut i = i + 1;
+        while i < self.tree.len() {
+            self.tree[i] = M::combine(&self.tree[i], &x);
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    // [0, end) 的聚合值
+    pub fn prefix(&self, end: usize) -> M::Item {
+        assert!(end <= self.len(), "end={} out of bounds, len={}", end, self.len());
+        let mut r = M::identity();
+        let mut i = end;
+        while i > 0 {
+            r = M::combine(&r, &self.tree[i]);
+            i -= i & i.wrapping_neg();
+        }
+        r
+    }
+}
+
+impl<M: Group> FenwickTree<M> {
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> M::Item {
+        let (l, r) = _bounds(&range, self.len());
+        M::combine(&self.prefix(r), &M::inverse(&self.prefix(l)))
+    }
+
+    pub fn get(&self, i: usize) -> M::Item { self.query(i..=i) }
+
+    pub fn set(&mut self, i: usize, x: M::Item) {
+        let delta = M::combine(&x, &M::inverse(&self.get(i)));
+        self.apply(i, delta);
+    }
+}
+
+// 支持区间修改 + 区间查询的树状数组，用两个差分数组实现：
+// prefix(p) = pow(d1.prefix(p), p) - d2.prefix(p)
+pub struct RangeFenwick<M: Group> {
+    d1: FenwickTree<M>,
+    d2: FenwickTree<M>,
+}
+
+impl<M: Group> RangeFenwick<M> {
+    pub fn new(n: usize) -> Self {
+        Self { d1: FenwickTree::new(n + 1), d2: FenwickTree::new(n + 1) }
+    }
+
+    pub fn from_vec(vals: Vec<M::Item>) -> Self {
+        let mut rf = Self::new(vals.len());
+        for (i, x) in vals.into_iter().enumerate() {
+            rf.apply(i, x);
+        }
+        rf
+    }
+
+    pub fn len(&self) -> usize { self.d1.len() - 1 }
+
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    pub fn apply(&mut self, i: usize, x: M::Item) { self.range_apply(i..=i, x) }
+
+    pub fn range_apply<R: RangeBounds<usize>>(&mut self, range: R, x: M::Item) {
+        let (l, r) = _bounds(&range, self.len());
+        let inv = M::inverse(&x);
+        self.d1.apply(l, x.clone());
+        self.d1.apply(r, inv.clone());
+        self.d2.apply(l, M::pow(&x, l));
+        self.d2.apply(r, M::pow(&inv, r));
+    }
+
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> M::Item {
+        let (l, r) = _bounds(&range, self.len());
+        M::combine(&self._prefix(r), &M::inverse(&self._prefix(l)))
+    }
+
+    pub fn get(&self, i: usize) -> M::Item { self.query(i..=i) }
+
+    fn _prefix(&self, p: usize) -> M::Item {
+        M::combine(&M::pow(&self.d1.prefix(p), p), &M::inverse(&self.d2.prefix(p)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::Lcg;
+
+    fn naive<M: Monoid>(vals: &[M::Item], l: usize, r: usize) -> M::Item {
+        vals[l..r].iter().fold(M::identity(), |acc, x| M::combine(&acc, x))
+    }
+
+    // 对照朴素数组随机执行单点赋值、区间修改与区间查询
+    fn random_segment<M: Monoid>(gen: fn(u32) -> M::Item)
+        where M::Item: PartialEq + ::std::fmt::Debug
+    {
+        let mut rng = Lcg::new(1);
+        let n = 57;
+        let mut vals: Vec<M::Item> = (0..n).map(|_| gen(rng.next_u32() >> 16)).collect();
+        let mut st = SegmentTree::<M>::from_vec(vals.clone());
+        for _ in 0..2000 {
+            let a = rng.below(n as u32 + 1) as usize;
+            let b = rng.below(n as u32 + 1) as usize;
+            let (l, r) = (a.min(b), a.max(b));
+            let x = gen(rng.next_u32() >> 16);
+            match rng.below(3) {
+                0 => {
+                    if l < n {
+                        st.set(l, x.clone());
+                        vals[l] = x;
+                    }
+                }
+                1 => {
+                    st.range_apply(l..r, x.clone());
+                    for v in vals[l..r].iter_mut() {
+                        *v = M::combine(v, &x);
+                    }
+                }
+                _ => assert_eq!(naive::<M>(&vals, l, r), st.query(l..r)),
+            }
+        }
+    }
+
+    #[test]
+    fn segment_tree() {
+        random_segment::<Sum>(|x| x as i64 % 100 - 50);
+        random_segment::<Min>(|x| x as i64 % 1000);
+        random_segment::<Max>(|x| x as i64 % 1000);
+        random_segment::<Gcd>(|x| u64::from(x % 12 + 1) * 6);
+
+        let mut st = SegmentTree::<Sum>::new(10);
+        st.range_apply(2..8, 3);
+        st.apply(5, 10);
+        assert_eq!(28, st.query(..));
+        assert_eq!(13, st.get(5));
+        assert_eq!(6, st.query(6..=7));
+        assert_eq!(0, st.query(3..3));
+
+        // 查询只需要共享引用，标记仍留在祖先节点上
+        let view = &st;
+        assert!(view.lazy.iter().any(Option::is_some));
+        assert_eq!(19, view.query(4..7));
+        assert_eq!(3, view.get(2));
+    }
+
+    #[test]
+    fn fenwick_tree() {
+        let mut ft = FenwickTree::<Sum>::from_vec(vec![5, 3, -2, 7, 1]);
+        assert_eq!(14, ft.query(..));
+        assert_eq!(8, ft.query(1..4));
+        ft.apply(2, 10);
+        ft.set(0, 1);
+        assert_eq!(1, ft.get(0));
+        assert_eq!(20, ft.query(..));
+
+        // Max 不是 Group，只能做前缀查询
+        let mut ft = FenwickTree::<Max>::from_vec(vec![4, 9, 2, 6]);
+        assert_eq!(9, ft.prefix(4));
+        assert_eq!(4, ft.prefix(1));
+        ft.apply(0, 11);
+        assert_eq!(11, ft.prefix(1));
+
+        let gcd = FenwickTree::<Gcd>::from_vec(vec![12, 18, 27]);
+        assert_eq!(6, gcd.prefix(2));
+        assert_eq!(3, gcd.prefix(3));
+    }
+
+    #[test]
+    fn range_fenwick() {
+        let mut rng = Lcg::new(9);
+        let n = 40;
+        let mut vals: Vec<i64> = (0..n).map(|_| i64::from(rng.below(100))).collect();
+        let mut rf = RangeFenwick::<Sum>::from_vec(vals.clone());
+        for _ in 0..1000 {
+            let a = rng.below(n as u32 + 1) as usize;
+            let b = rng.below(n as u32 + 1) as usize;
+            let (l, r) = (a.min(b), a.max(b));
+            if rng.below(2) == 0 {
+                let x = i64::from(rng.below(21)) - 10;
+                rf.range_apply(l..r, x);
+                for v in vals[l..r].iter_mut() {
+                    *v += x;
+                }
+            } else {
+                assert_eq!(vals[l..r].iter().sum::<i64>(), rf.query(l..r));
+            }
+        }
+    }
+}
@@ -0,0 +1,590 @@
+// B+树：节点连续存放在 Vec 中，以下标互相引用，叶子节点按顺序单向链接
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+
+use tree::search::Lookup;
+
+const DEFAULT_ORDER: usize = 32;
+
+struct InternalNode {
+    keys: Vec<String>,
+    children: Vec<usize>,
+}
+
+struct LeafNode<V> {
+    keys: Vec<String>,
+    vals: Vec<V>,
+    next: Option<usize>,
+}
+
+enum Node<V> {
+    Internal(InternalNode),
+    Leaf(LeafNode<V>),
+    // 已释放，等待复用
+    Vacant,
+}
+
+pub struct BPlusTree<V> {
+    nodes: Vec<Node<V>>,
+    free: Vec<usize>,
+    root: usize,
+    order: usize,
+    len: usize,
+}
+
+// 内部节点中 key 应进入的子树下标：等于分隔键时进入右子树
+fn _child_index(keys: &[String], key: &str) -> usize {
+    match keys.binary_search_by(|k| k.as_str().cmp(key)) {
+        Ok(i) => i + 1,
+        Err(i) => i,
+    }
+}
+
+impl<V> Default for BPlusTree<V> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<V> BPlusTree<V> {
+    pub fn new() -> Self { Self::with_order(DEFAULT_ORDER) }
+
+    // order 为内部节点的最大子节点数，叶子最多存放 order - 1 个键
+    pub fn with_order(order: usize) -> Self {
+        assert!(order >= 3, "order={} is too small, at least 3", order);
+        let leaf = LeafNode { keys: Vec::new(), vals: Vec::new(), next: None };
+        Self {
+            nodes: vec![Node::Leaf(leaf)],
+            free: Vec::new(),
+            root: 0,
+            order,
+            len: 0,
+        }
+    }
+
+    pub fn order(&self) -> usize { self.order }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    pub fn find(&self, key: &str) -> Option<&V> {
+        let leaf = self.leaf(self._find_leaf(key));
+        match leaf.keys.binary_search_by(|k| k.as_str().cmp(key)) {
+            Ok(i) => Some(&leaf.vals[i]),
+            Err(_) => None,
+        }
+    }
+
+    pub fn find_mut(&mut self, key: &str) -> Option<&mut V> {
+        let idx = self._find_leaf(key);
+        let leaf = self.leaf_mut(idx);
+        match leaf.keys.binary_search_by(|k| k.as_str().cmp(key)) {
+            Ok(i) => Some(&mut leaf.vals[i]),
+            Err(_) => None,
+        }
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool { self.find(key).is_some() }
+
+    // 插入，key 已存在时替换并返回旧值
+    pub fn add(&mut self, key: String, value: V) -> Option<V> {
+        let root = self.root;
+        let (old, split) = self._insert(root, key, value);
+        if let Some((sep, right)) = split {
+            self.root = self.alloc(Node::Internal(InternalNode {
+                keys: vec![sep],
+                children: vec![root, right],
+            }));
+        }
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn update(&mut self, key: &str, value: V) -> Result<(), String> {
+        match self.find_mut(key) {
+            None => Err(format!("node={} not exists", key)),
+            Some(v) => {
+                *v = value;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let root = self.root;
+        let r = self._remove(root, key);
+        if r.is_some() {
+            self.len -= 1;
+            // 根节点只剩一个孩子时降低一层
+            let only_child = match self.nodes[root] {
+                Node::Internal(ref n) if n.children.len() == 1 => Some(n.children[0]),
+                _ => None,
+            };
+            if let Some(child) = only_child {
+                self.release(root);
+                self.root = child;
+            }
+        }
+        r
+    }
+
+    pub fn min_val(&self) -> Option<&V> {
+        self.leaf(self._first_leaf()).vals.first()
+    }
+
+    pub fn max_val(&self) -> Option<&V> {
+        let mut cur = self.root;
+        loop {
+            match self.nodes[cur] {
+                Node::Internal(ref n) => cur = *n.children.last().unwrap(),
+                Node::Leaf(ref l) => break l.vals.last(),
+                Node::Vacant => unreachable!(),
+            }
+        }
+    }
+
+    // 根到叶子的边数，空树为 -1
+    pub fn height(&self) -> i32 {
+        if self.is_empty() {
+            return -1;
+        }
+        let mut h = 0;
+        let mut cur = self.root;
+        while let Node::Internal(ref n) = self.nodes[cur] {
+            cur = n.children[0];
+            h += 1;
+        }
+        h
+    }
+
+    // 按键升序遍历区间内的元素，定位起点后沿叶子链表扫描
+    pub fn range<R: RangeBounds<String>>(&self, range: R) -> Range<'_, V> {
+        let (leaf, pos) = match range.start_bound() {
+            Bound::Unbounded => (self._first_leaf(), 0),
+            Bound::Included(k) => {
+                let idx = self._find_leaf(k);
+                let keys = &self.leaf(idx).keys;
+                (idx, keys.binary_search(k).unwrap_or_else(|i| i))
+            }
+            Bound::Excluded(k) => {
+                let idx = self._find_leaf(k);
+                let keys = &self.leaf(idx).keys;
+                (idx, keys.binary_search(k).map(|i| i + 1).unwrap_or_else(|i| i))
+            }
+        };
+        Range {
+            tree: self,
+            leaf: Some(leaf),
+            pos,
+            end: range.end_bound().cloned(),
+        }
+    }
+
+    pub fn iter(&self) -> Range<'_, V> { self.range(..) }
+
+    fn leaf(&self, idx: usize) -> &LeafNode<V> {
+        match self.nodes[idx] {
+            Node::Leaf(ref l) => l,
+            _ => unreachable!("node={} is not a leaf", idx),
+        }
+    }
+
+    fn leaf_mut(&mut self, idx: usize) -> &mut LeafNode<V> {
+        match self.nodes[idx] {
+            Node::Leaf(ref mut l) => l,
+            _ => unreachable!("node={} is not a leaf", idx),
+        }
+    }
+
+    fn internal(&self, idx: usize) -> &InternalNode {
+        match self.nodes[idx] {
+            Node::Internal(ref n) => n,
+            _ => unreachable!("node={} is not an internal node", idx),
+        }
+    }
+
+    fn internal_mut(&mut self, idx: usize) -> &mut InternalNode {
+        match self.nodes[idx] {
+            Node::Internal(ref mut n) => n,
+            _ => unreachable!("node={} is not an internal node", idx),
+        }
+    }
+
+    fn is_leaf(&self, idx: usize) -> bool {
+        matches!(self.nodes[idx], Node::Leaf(_))
+    }
+
+    fn alloc(&mut self, node: Node<V>) -> usize {
+        match self.free.pop() {
+            Some(idx) => {
+                self.nodes[idx] = node;
+                idx
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn release(&mut self, idx: usize) -> Node<V> {
+        self.free.push(idx);
+        mem::replace(&mut self.nodes[idx], Node::Vacant)
+    }
+
+    fn min_leaf_keys(&self) -> usize { self.order / 2 }
+
+    fn min_children(&self) -> usize { self.order.div_ceil(2) }
+
+    fn _underflow(&self, idx: usize) -> bool {
+        match self.nodes[idx] {
+            Node::Leaf(ref l) => l.keys.len() < self.min_leaf_keys(),
+            Node::Internal(ref n) => n.children.len() < self.min_children(),
+            Node::Vacant => unreachable!(),
+        }
+    }
+
+    fn _can_lend(&self, idx: usize) -> bool {
+        match self.nodes[idx] {
+            Node::Leaf(ref l) => l.keys.len() > self.min_leaf_keys(),
+            Node::Internal(ref n) => n.children.len() > self.min_children(),
+            Node::Vacant => unreachable!(),
+        }
+    }
+
+    fn _find_leaf(&self, key: &str) -> usize {
+        let mut cur = self.root;
+        while let Node::Internal(ref n) = self.nodes[cur] {
+            cur = n.children[_child_index(&n.keys, key)];
+        }
+        cur
+    }
+
+    fn _first_leaf(&self) -> usize {
+        let mut cur = self.root;
+        while let Node::Internal(ref n) = self.nodes[cur] {
+            cur = n.children[0];
+        }
+        cur
+    }
+
+    // 返回 (被替换的旧值, 分裂出的 (分隔键, 右节点))
+    fn _insert(&mut self, idx: usize, key: String, value: V) -> (Option<V>, Option<(String, usize)>) {
+        if self.is_leaf(idx) {
+            let max_keys = self.order - 1;
+            let leaf = self.leaf_mut(idx);
+            match leaf.keys.binary_search(&key) {
+                Ok(i) => return (Some(mem::replace(&mut leaf.vals[i], value)), None),
+                Err(i) => {
+                    leaf.keys.insert(i, key);
+                    leaf.vals.insert(i, value);
+                }
+            }
+            if leaf.keys.len() <= max_keys {
+                return (None, None);
+            }
+
+            let mid = leaf.keys.len() / 2;
+            let keys = leaf.keys.split_off(mid);
+            let vals = leaf.vals.split_off(mid);
+            let next = leaf.next;
+            let sep = keys[0].clone();
+            let right = self.alloc(Node::Leaf(LeafNode { keys, vals, next }));
+            self.leaf_mut(idx).next = Some(right);
+            return (None, Some((sep, right)));
+        }
+
+        let i = _child_index(&self.internal(idx).keys, &key);
+        let child = self.internal(idx).children[i];
+        let (old, split) = self._insert(child, key, value);
+        if let Some((sep, right)) = split {
+            let order = self.order;
+            let node = self.internal_mut(idx);
+            node.keys.insert(i, sep);
+            node.children.insert(i + 1, right);
+            if node.children.len() > order {
+                let mid = node.keys.len() / 2;
+                let mut keys = node.keys.split_off(mid);
+                let children = node.children.split_off(mid + 1);
+                let up = keys.remove(0);
+                let r = self.alloc(Node::Internal(InternalNode { keys, children }));
+                return (old, Some((up, r)));
+            }
+        }
+        (old, None)
+    }
+
+    fn _remove(&mut self, idx: usize, key: &str) -> Option<V> {
+        if self.is_leaf(idx) {
+            let leaf = self.leaf_mut(idx);
+            return match leaf.keys.binary_search_by(|k| k.as_str().cmp(key)) {
+                Ok(i) => {
+                    leaf.keys.remove(i);
+                    Some(leaf.vals.remove(i))
+                }
+                Err(_) => None,
+            };
+        }
+
+        let i = _child_index(&self.internal(idx).keys, key);
+        let child = self.internal(idx).children[i];
+        let r = self._remove(child, key);
+        if r.is_some() && self._underflow(child) {
+            self._fix_child(idx, i);
+        }
+        r
+    }
+
+    // 子节点不足半满：优先向兄弟借，借不到则与兄弟合并
+    fn _fix_child(&mut self, parent: usize, i: usize) {
+        let children = self.internal(parent).children.clone();
+        if i > 0 && self._can_lend(children[i - 1]) {
+            self._borrow_from_left(parent, i);
+        } else if i + 1 < children.len() && self._can_lend(children[i + 1]) {
+            self._borrow_from_right(parent, i);
+        } else if i > 0 {
+            self._merge(parent, i - 1);
+        } else {
+            self._merge(parent, i);
+        }
+    }
+
+    fn _borrow_from_left(&mut self, parent: usize, i: usize) {
+        let left = self.internal(parent).children[i - 1];
+        let cur = self.internal(parent).children[i];
+        if self.is_leaf(cur) {
+            let (k, v) = {
+                let l = self.leaf_mut(left);
+                (l.keys.pop().unwrap(), l.vals.pop().unwrap())
+            };
+            self.internal_mut(parent).keys[i - 1] = k.clone();
+            let c = self.leaf_mut(cur);
+            c.keys.insert(0, k);
+            c.vals.insert(0, v);
+        } else {
+            let (k, child) = {
+                let l = self.internal_mut(left);
+                (l.keys.pop().unwrap(), l.children.pop().unwrap())
+            };
+            let sep = mem::replace(&mut self.internal_mut(parent).keys[i - 1], k);
+            let c = self.internal_mut(cur);
+            c.keys.insert(0, sep);
+            c.children.insert(0, child);
+        }
+    }
+
+    fn _borrow_from_right(&mut self, parent: usize, i: usize) {
+        let cur = self.internal(parent).children[i];
+        let right = self.internal(parent).children[i + 1];
+        if self.is_leaf(cur) {
+            let (k, v, first) = {
+                let r = self.leaf_mut(right);
+                let k = r.keys.remove(0);
+                let v = r.vals.remove(0);
+                (k, v, r.keys[0].clone())
+            };
+            self.internal_mut(parent).keys[i] = first;
+            let c = self.leaf_mut(cur);
+            c.keys.push(k);
+            c.vals.push(v);
+        } else {
+            let (k, child) = {
+                let r = self.internal_mut(right);
+                (r.keys.remove(0), r.children.remove(0))
+            };
+            let sep = mem::replace(&mut self.internal_mut(parent).keys[i], k);
+            let c = self.internal_mut(cur);
+            c.keys.push(sep);
+            c.children.push(child);
+        }
+    }
+
+    // 把 children[j + 1] 合并进 children[j]
+    fn _merge(&mut self, parent: usize, j: usize) {
+        let (sep, left, right) = {
+            let p = self.internal_mut(parent);
+            let right = p.children.remove(j + 1);
+            (p.keys.remove(j), p.children[j], right)
+        };
+        match self.release(right) {
+            Node::Leaf(r) => {
+                let l = self.leaf_mut(left);
+                l.keys.extend(r.keys);
+                l.vals.extend(r.vals);
+                l.next = r.next;
+            }
+            Node::Internal(r) => {
+                let l = self.internal_mut(left);
+                l.keys.push(sep);
+                l.keys.extend(r.keys);
+                l.children.extend(r.children);
+            }
+            Node::Vacant => unreachable!(),
+        }
+    }
+}
+
+impl<V: Clone> BPlusTree<V> {
+    pub fn find_and_clone(&self, key: &str) -> Option<V> { self.find(key).cloned() }
+
+    pub fn min_val_clone(&self) -> Option<V> { self.min_val().cloned() }
+
+    pub fn max_val_clone(&self) -> Option<V> { self.max_val().cloned() }
+}
+
+#[allow(clippy::ptr_arg)]
+impl<V: Clone> Lookup<V> for BPlusTree<V> {
+    fn add(&mut self, key: String, value: V) -> Option<V> { BPlusTree::add(self, key, value) }
+
+    fn remove(&mut self, key: &String) -> Option<V> { BPlusTree::remove(self, key) }
+
+    fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &String, f: F) -> Option<R> { self.find(key).map(f) }
+
+    fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, key: &String, f: F) -> Option<R> {
+        self.find_mut(key).map(f)
+    }
+}
+
+pub struct Range<'a, V: 'a> {
+    tree: &'a BPlusTree<V>,
+    leaf: Option<usize>,
+    pos: usize,
+    end: Bound<String>,
+}
+
+impl<'a, V> Iterator for Range<'a, V> {
+    type Item = (&'a String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tree = self.tree;
+        loop {
+            let leaf = tree.leaf(self.leaf?);
+            if self.pos >= leaf.keys.len() {
+                self.leaf = leaf.next;
+                self.pos = 0;
+                continue;
+            }
+
+            let key = &leaf.keys[self.pos];
+            let past_end = match self.end {
+                Bound::Included(ref e) => key > e,
+                Bound::Excluded(ref e) => key >= e,
+                Bound::Unbounded => false,
+            };
+            if past_end {
+                self.leaf = None;
+                return None;
+            }
+            self.pos += 1;
+            return Some((key, &leaf.vals[self.pos - 1]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::check_against_model;
+
+    // 校验键有序、节点大小、叶子同层以及叶子链表
+    fn check<V>(tree: &BPlusTree<V>) {
+        fn walk<V>(tree: &BPlusTree<V>, idx: usize, depth: usize, leaves: &mut Vec<(usize, usize)>) {
+            match tree.nodes[idx] {
+                Node::Leaf(ref l) => {
+                    if idx != tree.root {
+                        assert!(l.keys.len() >= tree.min_leaf_keys());
+                    }
+                    assert!(l.keys.len() < tree.order);
+                    assert!(l.keys.windows(2).all(|w| w[0] < w[1]));
+                    leaves.push((idx, depth));
+                }
+                Node::Internal(ref n) => {
+                    if idx != tree.root {
+                        assert!(n.children.len() >= tree.min_children());
+                    }
+                    assert!(n.children.len() <= tree.order);
+                    assert_eq!(n.keys.len() + 1, n.children.len());
+                    for c in n.children.iter() {
+                        walk(tree, *c, depth + 1, leaves);
+                    }
+                }
+                Node::Vacant => panic!("vacant node={} is reachable", idx),
+            }
+        }
+
+        let mut leaves = Vec::new();
+        walk(tree, tree.root, 0, &mut leaves);
+        assert!(leaves.iter().all(|&(_, d)| d == leaves[0].1));
+
+        let mut cur = Some(tree._first_leaf());
+        for &(idx, _) in leaves.iter() {
+            assert_eq!(Some(idx), cur);
+            cur = tree.leaf(idx).next;
+        }
+        assert_eq!(None, cur);
+
+        let keys: Vec<&String> = tree.iter().map(|(k, _)| k).collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(tree.len(), keys.len());
+    }
+
+    #[test]
+    fn add_find_remove() {
+        let mut tree = BPlusTree::with_order(3);
+        for i in 0..100 {
+            assert_eq!(None, tree.add(format!("{:03}", i), i));
+            check(&tree);
+        }
+        assert_eq!(100, tree.len());
+        assert_eq!(Some(&42), tree.find("042"));
+        assert_eq!(None, tree.find("100"));
+        assert_eq!(Some(42), tree.add(String::from("042"), -42));
+        assert_eq!(Some(-42), tree.find_and_clone("042"));
+        assert!(tree.update("100", 100).is_err());
+        assert!(tree.update("000", -1).is_ok());
+        assert_eq!(Some(&-1), tree.min_val());
+        assert_eq!(Some(&99), tree.max_val());
+
+        for i in (0..100).filter(|i| i % 3 != 0) {
+            assert!(tree.remove(&format!("{:03}", i)).is_some());
+            check(&tree);
+        }
+        assert_eq!(None, tree.remove("001"));
+        assert_eq!(34, tree.len());
+        for i in (0..100).filter(|i| i % 3 == 0) {
+            assert!(tree.remove(&format!("{:03}", i)).is_some());
+            check(&tree);
+        }
+        assert!(tree.is_empty());
+        assert_eq!(-1, tree.height());
+        assert_eq!(None, tree.min_val());
+    }
+
+    #[test]
+    fn range_scan() {
+        let mut tree = BPlusTree::with_order(4);
+        for i in 0..50 {
+            tree.add(format!("{:02}", i * 2), i * 2);
+        }
+
+        let vals: Vec<i32> = tree.range(String::from("10")..String::from("20")).map(|(_, v)| *v).collect();
+        assert_eq!(vec![10, 12, 14, 16, 18], vals);
+        let vals: Vec<i32> = tree.range(String::from("11")..=String::from("20")).map(|(_, v)| *v).collect();
+        assert_eq!(vec![12, 14, 16, 18, 20], vals);
+        let vals: Vec<i32> = tree.range((Bound::Excluded(String::from("94")), Bound::Unbounded)).map(|(_, v)| *v).collect();
+        assert_eq!(vec![96, 98], vals);
+        assert_eq!(0, tree.range(String::from("99")..).count());
+        assert_eq!(50, tree.iter().count());
+    }
+
+    #[test]
+    fn random_against_btreemap() {
+        let mut tree = BPlusTree::with_order(5);
+        let model = check_against_model(&mut tree, 43, 5000, 500);
+        check(&tree);
+        let expect: Vec<(&String, &u32)> = model.iter().collect();
+        let actual: Vec<(&String, &u32)> = tree.iter().collect();
+        assert_eq!(expect, actual);
+    }
+}
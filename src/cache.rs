@@ -0,0 +1,429 @@
+// 容量受限的缓存：LRU 与 LFU，哈希表索引 + 侵入式双向链表，链表与 BinaryNode 一样用 Rc 指向后继、Weak 指回前驱
+use std::cell::{Ref, RefCell};
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+// 淘汰回调，参数为被淘汰的 key 和 value；主动 remove 不触发
+pub type EvictFn<V> = Box<dyn FnMut(&str, V)>;
+
+type EntryRc<V> = Rc<RefCell<Entry<V>>>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 { 0.0 } else { self.hits as f64 / total as f64 }
+    }
+}
+
+struct Entry<V> {
+    key: String,
+    value: V,
+    // 只有 LFU 使用
+    freq: usize,
+    prev: Option<Weak<RefCell<Entry<V>>>>,
+    next: Option<EntryRc<V>>,
+}
+
+// 头部为最近使用，尾部为最久未使用
+struct List<V> {
+    head: Option<EntryRc<V>>,
+    tail: Option<Weak<RefCell<Entry<V>>>>,
+    len: usize,
+}
+
+impl<V> List<V> {
+    fn new() -> Self { Self { head: None, tail: None, len: 0 } }
+
+    fn push_front(&mut self, entry: EntryRc<V>) {
+        {
+            let mut e = entry.as_ref().borrow_mut();
+            e.prev = None;
+            e.next = self.head.take();
+        }
+        match entry.as_ref().borrow().next {
+            Some(ref n) => n.as_ref().borrow_mut().prev = Some(Rc::downgrade(&entry)),
+            None => self.tail = Some(Rc::downgrade(&entry)),
+        }
+        self.head = Some(entry);
+        self.len += 1;
+    }
+
+    fn unlink(&mut self, entry: &EntryRc<V>) {
+        let prev = entry.as_ref().borrow_mut().prev.take().and_then(|w| w.upgrade());
+        let next = entry.as_ref().borrow_mut().next.take();
+        match next {
+            Some(ref n) => n.as_ref().borrow_mut().prev = prev.as_ref().map(Rc::downgrade),
+            None => self.tail = prev.as_ref().map(Rc::downgrade),
+        }
+        match prev {
+            Some(ref p) => p.as_ref().borrow_mut().next = next,
+            None => self.head = next,
+        }
+        self.len -= 1;
+    }
+
+    fn pop_back(&mut self) -> Option<EntryRc<V>> {
+        let tail = self.tail.as_ref().and_then(|w| w.upgrade())?;
+        self.unlink(&tail);
+        Some(tail)
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let mut keys = Vec::with_capacity(self.len);
+        let mut cur = self.head.clone();
+        while let Some(e) = cur {
+            keys.push(e.as_ref().borrow().key.clone());
+            cur = e.as_ref().borrow().next.clone();
+        }
+        keys
+    }
+}
+
+// 逐个断开后继，避免长链表递归析构
+impl<V> Drop for List<V> {
+    fn drop(&mut self) {
+        let mut cur = self.head.take();
+        while let Some(e) = cur {
+            cur = e.as_ref().borrow_mut().next.take();
+        }
+    }
+}
+
+fn _new_entry<V>(key: String, value: V) -> EntryRc<V> {
+    Rc::new(RefCell::new(Entry {
+        key,
+        value,
+        freq: 1,
+        prev: None,
+        next: None,
+    }))
+}
+
+// 已从索引和链表摘下的项只剩这一个强引用，拆出 key 和 value
+fn _take_entry<V>(entry: EntryRc<V>) -> Entry<V> {
+    match Rc::try_unwrap(entry) {
+        Ok(cell) => cell.into_inner(),
+        Err(_) => panic!("entry still referenced after unlink"),
+    }
+}
+
+// 借出项里的值，借用期间缓存不可修改
+fn _value<V>(entry: &EntryRc<V>) -> Ref<'_, V> {
+    Ref::map(entry.as_ref().borrow(), |e| &e.value)
+}
+
+pub struct LruCache<V> {
+    capacity: usize,
+    index: HashMap<String, EntryRc<V>>,
+    list: List<V>,
+    stats: CacheStats,
+    on_evict: Option<EvictFn<V>>,
+}
+
+impl<V> LruCache<V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            capacity,
+            index: HashMap::new(),
+            list: List::new(),
+            stats: CacheStats::default(),
+            on_evict: None,
+        }
+    }
+
+    pub fn with_evict(capacity: usize, f: EvictFn<V>) -> Self {
+        let mut cache = Self::new(capacity);
+        cache.on_evict = Some(f);
+        cache
+    }
+
+    pub fn capacity(&self) -> usize { self.capacity }
+
+    pub fn len(&self) -> usize { self.index.len() }
+
+    pub fn is_empty(&self) -> bool { self.index.is_empty() }
+
+    pub fn stats(&self) -> CacheStats { self.stats }
+
+    pub fn contains_key(&self, key: &str) -> bool { self.index.contains_key(key) }
+
+    // 命中时移到链表头部并计入统计
+    pub fn get(&mut self, key: &str) -> Option<Ref<'_, V>> {
+        let entry = match self.index.get(key) {
+            None => {
+                self.stats.misses += 1;
+                return None;
+            }
+            Some(e) => Rc::clone(e),
+        };
+        self.stats.hits += 1;
+        self.list.unlink(&entry);
+        self.list.push_front(entry);
+        self.peek(key)
+    }
+
+    // 只读查看，不改变顺序也不计入统计
+    pub fn peek(&self, key: &str) -> Option<Ref<'_, V>> {
+        self.index.get(key).map(_value)
+    }
+
+    // 写入，key 已存在时替换并返回旧值；超出容量时淘汰最久未使用的项
+    pub fn put(&mut self, key: String, value: V) -> Option<V> {
+        if let Some(entry) = self.index.get(&key).cloned() {
+            self.list.unlink(&entry);
+            self.list.push_front(Rc::clone(&entry));
+            let old = ::std::mem::replace(&mut entry.as_ref().borrow_mut().value, value);
+            return Some(old);
+        }
+        if self.index.len() == self.capacity {
+            let victim = self.list.pop_back().unwrap();
+            self._evict(victim);
+        }
+        let entry = _new_entry(key.clone(), value);
+        self.list.push_front(Rc::clone(&entry));
+        self.index.insert(key, entry);
+        None
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let entry = self.index.remove(key)?;
+        self.list.unlink(&entry);
+        Some(_take_entry(entry).value)
+    }
+
+    // 从最近使用到最久未使用
+    pub fn keys(&self) -> Vec<String> { self.list.keys() }
+
+    fn _evict(&mut self, victim: EntryRc<V>) {
+        self.index.remove(&victim.as_ref().borrow().key);
+        self.stats.evictions += 1;
+        let e = _take_entry(victim);
+        if let Some(ref mut f) = self.on_evict {
+            f(&e.key, e.value);
+        }
+    }
+}
+
+// 淘汰访问次数最少的项；次数相同时淘汰其中最久未使用的
+pub struct LfuCache<V> {
+    capacity: usize,
+    index: HashMap<String, EntryRc<V>>,
+    // 每个访问次数一条链表
+    buckets: HashMap<usize, List<V>>,
+    min_freq: usize,
+    stats: CacheStats,
+    on_evict: Option<EvictFn<V>>,
+}
+
+impl<V> LfuCache<V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            capacity,
+            index: HashMap::new(),
+            buckets: HashMap::new(),
+            min_freq: 0,
+            stats: CacheStats::default(),
+            on_evict: None,
+        }
+    }
+
+    pub fn with_evict(capacity: usize, f: EvictFn<V>) -> Self {
+        let mut cache = Self::new(capacity);
+        cache.on_evict = Some(f);
+        cache
+    }
+
+    pub fn capacity(&self) -> usize { self.capacity }
+
+    pub fn len(&self) -> usize { self.index.len() }
+
+    pub fn is_empty(&self) -> bool { self.index.is_empty() }
+
+    pub fn stats(&self) -> CacheStats { self.stats }
+
+    pub fn contains_key(&self, key: &str) -> bool { self.index.contains_key(key) }
+
+    // key 的访问次数，不存在时为 None
+    pub fn frequency(&self, key: &str) -> Option<usize> {
+        self.index.get(key).map(|e| e.as_ref().borrow().freq)
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<Ref<'_, V>> {
+        let entry = match self.index.get(key) {
+            None => {
+                self.stats.misses += 1;
+                return None;
+            }
+            Some(e) => Rc::clone(e),
+        };
+        self.stats.hits += 1;
+        self._touch(&entry);
+        drop(entry);
+        self.peek(key)
+    }
+
+    pub fn peek(&self, key: &str) -> Option<Ref<'_, V>> {
+        self.index.get(key).map(_value)
+    }
+
+    pub fn put(&mut self, key: String, value: V) -> Option<V> {
+        if let Some(entry) = self.index.get(&key).cloned() {
+            self._touch(&entry);
+            let old = ::std::mem::replace(&mut entry.as_ref().borrow_mut().value, value);
+            return Some(old);
+        }
+        if self.index.len() == self.capacity {
+            let victim = self._bucket_remove(self.min_freq, None).unwrap();
+            self.index.remove(&victim.as_ref().borrow().key);
+            self.stats.evictions += 1;
+            let e = _take_entry(victim);
+            if let Some(ref mut f) = self.on_evict {
+                f(&e.key, e.value);
+            }
+        }
+        let entry = _new_entry(key.clone(), value);
+        self.buckets.entry(1).or_insert_with(List::new).push_front(Rc::clone(&entry));
+        self.min_freq = 1;
+        self.index.insert(key, entry);
+        None
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let entry = self.index.remove(key)?;
+        let freq = entry.as_ref().borrow().freq;
+        self._bucket_remove(freq, Some(&entry));
+        // min_freq 可能指向已删除的链表，下次淘汰前重新计算
+        if !self.buckets.contains_key(&self.min_freq) {
+            self.min_freq = self.buckets.keys().cloned().min().unwrap_or(0);
+        }
+        Some(_take_entry(entry).value)
+    }
+
+    // 各访问次数对应的 key，次数升序，同一次数内从最近到最久
+    pub fn keys_by_frequency(&self) -> Vec<(usize, Vec<String>)> {
+        let mut out: Vec<(usize, Vec<String>)> = self.buckets.iter().map(|(&f, l)| (f, l.keys())).collect();
+        out.sort_by_key(|&(f, _)| f);
+        out
+    }
+
+    // 从 freq 链表中摘下 entry（None 表示摘尾部），链表空了就删掉
+    fn _bucket_remove(&mut self, freq: usize, entry: Option<&EntryRc<V>>) -> Option<EntryRc<V>> {
+        let (removed, empty) = {
+            let list = self.buckets.get_mut(&freq)?;
+            let removed = match entry {
+                Some(e) => {
+                    list.unlink(e);
+                    Rc::clone(e)
+                }
+                None => list.pop_back()?,
+            };
+            (removed, list.len == 0)
+        };
+        if empty {
+            self.buckets.remove(&freq);
+        }
+        Some(removed)
+    }
+
+    // 访问次数加一，移到下一条链表头部
+    fn _touch(&mut self, entry: &EntryRc<V>) {
+        let freq = entry.as_ref().borrow().freq;
+        self._bucket_remove(freq, Some(entry));
+        if self.min_freq == freq && !self.buckets.contains_key(&freq) {
+            self.min_freq = freq + 1;
+        }
+        entry.as_ref().borrow_mut().freq = freq + 1;
+        self.buckets.entry(freq + 1).or_insert_with(List::new).push_front(Rc::clone(entry));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_cache() {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&evicted);
+        let mut cache = LruCache::with_evict(3, Box::new(move |k: &str, v: i32| {
+            log.borrow_mut().push((k.to_string(), v));
+        }));
+
+        for (i, k) in ["a", "b", "c"].iter().enumerate() {
+            assert!(cache.put(k.to_string(), i as i32).is_none());
+        }
+        assert_eq!(Some(0), cache.get("a").map(|v| *v));
+        assert!(cache.get("z").is_none());
+        assert_eq!(vec!["a", "c", "b"], cache.keys());
+
+        // b 最久未使用，被淘汰
+        cache.put(String::from("d"), 3);
+        assert!(!cache.contains_key("b"));
+        assert_eq!(Some(0), cache.put(String::from("a"), 10));
+        cache.put(String::from("e"), 4);
+        assert_eq!(vec![(String::from("b"), 1), (String::from("c"), 2)], *evicted.borrow());
+
+        assert_eq!(Some(3), cache.peek("d").map(|v| *v));
+        assert_eq!(vec!["e", "a", "d"], cache.keys());
+        assert_eq!(Some(3), cache.remove("d"));
+        assert_eq!(2, cache.len());
+        assert_eq!(CacheStats { hits: 1, misses: 1, evictions: 2 }, cache.stats());
+        assert_eq!(0.5, cache.stats().hit_rate());
+    }
+
+    #[test]
+    fn lfu_cache() {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&evicted);
+        let mut cache = LfuCache::with_evict(3, Box::new(move |k: &str, _: i32| {
+            log.borrow_mut().push(k.to_string());
+        }));
+
+        cache.put(String::from("a"), 1);
+        cache.put(String::from("b"), 2);
+        cache.put(String::from("c"), 3);
+        cache.get("a");
+        cache.get("a");
+        cache.get("b");
+        assert_eq!(Some(3), cache.frequency("a"));
+
+        // c 访问次数最少
+        cache.put(String::from("d"), 4);
+        // 次数都为 1 时淘汰较早的 d
+        cache.get("e");
+        cache.put(String::from("e"), 5);
+        assert_eq!(vec!["c", "d"], *evicted.borrow());
+
+        assert_eq!(
+            vec![(1, vec![String::from("e")]), (2, vec![String::from("b")]), (3, vec![String::from("a")])],
+            cache.keys_by_frequency()
+        );
+        assert_eq!(Some(5), cache.remove("e"));
+        cache.put(String::from("f"), 6);
+        cache.put(String::from("g"), 7);
+        assert_eq!(vec!["c", "d", "f"], *evicted.borrow());
+        assert_eq!(CacheStats { hits: 3, misses: 1, evictions: 3 }, cache.stats());
+    }
+
+    #[test]
+    fn no_leak_after_drop() {
+        let mut cache = LruCache::new(100);
+        let mut weak = Vec::new();
+        for i in 0..1000 {
+            cache.put(format!("{}", i), i);
+            weak.push(Rc::downgrade(&cache.index[&format!("{}", i)]));
+        }
+        cache.get("950");
+        drop(cache);
+        assert!(weak.iter().all(|w| w.upgrade().is_none()));
+    }
+}
@@ -0,0 +1,327 @@
+// 并发有序表：按键区间分片，每个分片是一棵 BPlusTree，由各自的 RwLock 保护
+// 单键操作只锁一个分片；区间扫描按分片顺序同时持有所涉及分片的读锁，得到一致的结果
+// 写操作任何时刻最多持有一把锁，读锁总是按下标递增获取，因此不会死锁
+use std::ops::{Bound, RangeBounds};
+use std::sync::{RwLock, RwLockReadGuard};
+
+use btree::BPlusTree;
+
+pub struct ShardedMap<V> {
+    // splits[i] 是第 i + 1 个分片的最小键，严格递增
+    splits: Vec<String>,
+    shards: Vec<RwLock<BPlusTree<V>>>,
+}
+
+impl<V> Default for ShardedMap<V> {
+    fn default() -> Self { Self::new(16) }
+}
+
+impl<V> ShardedMap<V> {
+    // 按首字符把 ASCII 空间均分成 count 片，只适合首字符分布均匀的键；
+    // 键有公共前缀（如 "user:"）时会全部落进同一片，失去分片的意义，此时应使用 from_sample
+    pub fn new(count: usize) -> Self {
+        assert!((1..=128).contains(&count), "count={} out of range 1..=128", count);
+        let splits = (1..count).map(|i| ((i * 128 / count) as u8 as char).to_string()).collect();
+        Self::with_splits(splits)
+    }
+
+    // 以给定的分隔键切分，n 个分隔键得到 n + 1 个分片
+    pub fn with_splits(mut splits: Vec<String>) -> Self {
+        splits.sort();
+        splits.dedup();
+        let shards = (0..=splits.len()).map(|_| RwLock::new(BPlusTree::new())).collect();
+        Self { splits, shards }
+    }
+
+    // 取样本键的 count 分位点作为分隔键，使各分片分到的键数大致相同；
+    // 样本中不同的键少于 count 个时分片也会相应变少
+    pub fn from_sample(mut sample: Vec<String>, count: usize) -> Self {
+        assert!(count >= 1, "count={} must be positive", count);
+        sample.sort();
+        sample.dedup();
+        let splits = (1..count).filter_map(|i| sample.get(i * sample.len() / count).cloned()).collect();
+        Self::with_splits(splits)
+    }
+
+    pub fn shard_count(&self) -> usize { self.shards.len() }
+
+    fn shard_of(&self, key: &str) -> usize {
+        self.splits.partition_point(|s| s.as_str() <= key)
+    }
+
+    fn shard(&self, key: &str) -> &RwLock<BPlusTree<V>> { &self.shards[self.shard_of(key)] }
+
+    // 同时持有全部分片的读锁，得到某一时刻的元素个数
+    pub fn len(&self) -> usize {
+        let guards: Vec<_> = self.shards.iter().map(|s| s.read().unwrap()).collect();
+        guards.iter().map(|g| g.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.shard(key).read().unwrap().contains_key(key)
+    }
+
+    // 在读锁内访问值，避免克隆
+    pub fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &str, f: F) -> Option<R> {
+        self.shard(key).read().unwrap().find(key).map(f)
+    }
+
+    // 插入，key 已存在时替换并返回旧值
+    pub fn insert(&self, key: String, value: V) -> Option<V> {
+        self.shard(&key).write().unwrap().add(key, value)
+    }
+
+    pub fn update(&self, key: &str, value: V) -> Result<(), String> {
+        self.shard(key).write().unwrap().update(key, value)
+    }
+
+    pub fn remove(&self, key: &str) -> Option<V> {
+        self.shard(key).write().unwrap().remove(key)
+    }
+
+    // 区间涉及的分片，按下标递增加读锁
+    fn read_range(&self, start: &Bound<String>, end: &Bound<String>) -> Vec<RwLockReadGuard<'_, BPlusTree<V>>> {
+        let lo = match *start {
+            Bound::Unbounded => 0,
+            Bound::Included(ref k) | Bound::Excluded(ref k) => self.shard_of(k),
+        };
+        let hi = match *end {
+            Bound::Unbounded => self.shards.len() - 1,
+            Bound::Included(ref k) | Bound::Excluded(ref k) => self.shard_of(k),
+        };
+        if lo > hi {
+            return Vec::new();
+        }
+        self.shards[lo..=hi].iter().map(|s| s.read().unwrap()).collect()
+    }
+}
+
+impl<V: Clone> ShardedMap<V> {
+    pub fn get(&self, key: &str) -> Option<V> { self.with_value(key, V::clone) }
+
+    // 区间扫描：返回的是某一时刻区间内全部元素的快照
+    pub fn range<R: RangeBounds<String>>(&self, range: R) -> Vec<(String, V)> {
+        let (start, end) = (range.start_bound().cloned(), range.end_bound().cloned());
+        let guards = self.read_range(&start, &end);
+        let mut out = Vec::new();
+        for g in guards.iter() {
+            out.extend(g.range((start.clone(), end.clone())).map(|(k, v)| (k.clone(), v.clone())));
+        }
+        out
+    }
+
+    pub fn to_vec(&self) -> Vec<(String, V)> { self.range(..) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, HashMap};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use testing::Lcg;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn single_thread_basics() {
+        assert_send_sync::<ShardedMap<String>>();
+
+        let map = ShardedMap::with_splits(vec![String::from("m"), String::from("f"), String::from("m")]);
+        assert_eq!(3, map.shard_count());
+        for k in &["apple", "fig", "kiwi", "mango", "peach", "grape"] {
+            assert_eq!(None, map.insert(k.to_string(), k.len()));
+        }
+        assert_eq!(Some(5), map.insert(String::from("apple"), 50));
+        assert_eq!(Some(50), map.get("apple"));
+        assert_eq!(Some(true), map.with_value("kiwi", |v| *v == 4));
+        assert!(map.update("lemon", 1).is_err());
+        assert_eq!(Some(5), map.remove("peach"));
+        assert_eq!(5, map.len());
+
+        let keys: Vec<String> = map.range(String::from("b")..=String::from("mango")).into_iter().map(|(k, _)| k).collect();
+        assert_eq!(vec!["fig", "grape", "kiwi", "mango"], keys);
+        assert!(map.range(String::from("z")..String::from("a")).is_empty());
+        assert_eq!(16, ShardedMap::<u8>::default().shard_count());
+
+        // 公共前缀的键按首字符切分全部挤在一片，按样本分位点切分则大致均匀
+        let keys: Vec<String> = (0..1000).map(|i| format!("user:{:04}", i)).collect();
+        let by_byte = ShardedMap::new(8);
+        let sampled = ShardedMap::from_sample(keys.iter().step_by(10).cloned().collect(), 8);
+        for k in keys.iter() {
+            by_byte.insert(k.clone(), ());
+            sampled.insert(k.clone(), ());
+        }
+        let sizes = |m: &ShardedMap<()>| m.shards.iter().map(|s| s.read().unwrap().len()).collect::<Vec<_>>();
+        assert_eq!(1, sizes(&by_byte).iter().filter(|&&n| n > 0).count());
+        assert_eq!(8, sampled.shard_count());
+        assert!(sizes(&sampled).iter().all(|&n| (100..=150).contains(&n)), "{:?}", sizes(&sampled));
+        assert_eq!(2, ShardedMap::<()>::from_sample(vec![String::from("a")], 4).shard_count());
+    }
+
+    // 一次调用的记录：调用前后从全局时钟各取一个时刻
+    struct Call {
+        inv: usize,
+        resp: usize,
+        key: String,
+        // 写入的值，None 表示这是一次读
+        wrote: Option<u64>,
+        // insert 返回的旧值或 get 读到的值
+        saw: Option<u64>,
+    }
+
+    // 多个线程在少量共享键上竞争 insert 与 get，写入的值全局唯一；
+    // insert 返回旧值，因此每个键的写入按 旧值 -> 新值 串成唯一的一条链，它就是线性化顺序。
+    // 检查：链覆盖全部写入且与实时顺序不矛盾，每次读到的值在它的调用区间内可能是当前值，最终值是链尾
+    #[test]
+    fn shared_keys_are_linearizable() {
+        const THREADS: u64 = 4;
+        const KEYS: [&str; 4] = ["apple", "kiwi", "plum", "zest"];
+        let map = Arc::new(ShardedMap::with_splits(vec![String::from("m")]));
+        let clock = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let (map, clock) = (Arc::clone(&map), Arc::clone(&clock));
+                thread::spawn(move || {
+                    let mut calls = Vec::new();
+                    let mut rng = Lcg::new(29 + t as u32);
+                    for i in 0..2000u64 {
+                        let seed = rng.next_u32();
+                        let key = KEYS[(seed >> 16) as usize % KEYS.len()].to_string();
+                        let wrote = if (seed >> 8) & 1 == 0 { Some(t * 1_000_000 + i) } else { None };
+                        let inv = clock.fetch_add(1, Ordering::SeqCst);
+                        let saw = match wrote {
+                            Some(v) => map.insert(key.clone(), v),
+                            None => map.get(&key),
+                        };
+                        let resp = clock.fetch_add(1, Ordering::SeqCst);
+                        calls.push(Call { inv, resp, key, wrote, saw });
+                    }
+                    calls
+                })
+            })
+            .collect();
+        let calls: Vec<Call> = handles.into_iter().flat_map(|h| h.join().unwrap()).collect();
+
+        for key in KEYS.iter() {
+            let (writes, reads): (Vec<&Call>, Vec<&Call>) = calls.iter().filter(|c| c.key == *key).partition(|c| c.wrote.is_some());
+            // 旧值 -> 写入，每个旧值至多被一次写入返回
+            let mut by_old = HashMap::new();
+            for w in writes.iter() {
+                assert!(by_old.insert(w.saw, *w).is_none(), "value {:?} overwritten twice", w.saw);
+            }
+            let mut chain: Vec<&Call> = Vec::new();
+            let mut cur = None;
+            while let Some(w) = by_old.get(&cur) {
+                chain.push(*w);
+                cur = w.wrote;
+            }
+            assert_eq!(writes.len(), chain.len(), "writes on {} do not form one chain", key);
+            assert_eq!(cur, map.get(key));
+
+            // 实时顺序：链上靠后的写入不能在靠前的写入开始之前就已结束
+            let mut max_inv = 0;
+            for w in chain.iter() {
+                assert!(w.resp > max_inv, "write {:?} on {} reordered", w.wrote, key);
+                max_inv = max_inv.max(w.inv);
+            }
+
+            // 读到 x：x 的写入在读结束前已开始，覆盖 x 的写入在读开始后才结束
+            let pos: HashMap<Option<u64>, usize> = chain.iter().enumerate().map(|(i, w)| (w.wrote, i)).collect();
+            for r in reads.iter() {
+                let next = match r.saw {
+                    None => chain.first(),
+                    Some(_) => {
+                        let i = pos[&r.saw];
+                        assert!(chain[i].inv < r.resp, "read {:?} on {} before its write", r.saw, key);
+                        chain.get(i + 1)
+                    }
+                };
+                assert!(next.is_none_or(|n| r.inv < n.resp), "stale read {:?} on {}", r.saw, key);
+            }
+        }
+    }
+
+    // 每个线程操作自己的一组键，所有返回值都要与该线程的顺序模型一致
+    #[test]
+    fn disjoint_keys_match_model() {
+        let map = Arc::new(ShardedMap::new(8));
+        let handles: Vec<_> = (0..8u32)
+            .map(|t| {
+                let map = Arc::clone(&map);
+                thread::spawn(move || {
+                    let mut model = BTreeMap::new();
+                    let mut rng = Lcg::new(17 + t);
+                    for _ in 0..3000 {
+                        let seed = rng.next_u32();
+                        // 首字符分散到各个分片
+                        let key = format!("{}{}#{}", (b'0' + ((seed >> 16) % 75) as u8) as char, (seed >> 8) % 40, t);
+                        match (seed >> 4) & 3 {
+                            0 => assert_eq!(model.remove(&key), map.remove(&key)),
+                            1 => assert_eq!(model.get(&key).cloned(), map.get(&key)),
+                            _ => assert_eq!(model.insert(key.clone(), seed), map.insert(key, seed)),
+                        }
+                    }
+                    model
+                })
+            })
+            .collect();
+
+        let mut expect = BTreeMap::new();
+        for h in handles {
+            expect.extend(h.join().unwrap());
+        }
+        assert_eq!(expect.into_iter().collect::<Vec<_>>(), map.to_vec());
+    }
+
+    // 写线程按键递增的顺序插入再按同样顺序删除，键跨越多个分片；
+    // 若区间扫描是原子的，每次扫描看到的某个写线程的键一定是连续的一段，且是前缀或后缀
+    #[test]
+    fn range_scans_are_atomic() {
+        const N: usize = 1000;
+        let splits = vec![String::from("0250"), String::from("0500"), String::from("0750")];
+        let map = Arc::new(ShardedMap::with_splits(splits));
+
+        let mut handles = Vec::new();
+        for t in 0..3usize {
+            let map = Arc::clone(&map);
+            handles.push(thread::spawn(move || {
+                for i in 0..N {
+                    map.insert(format!("{:04}-{}", i, t), i * 10 + t);
+                }
+                for i in 0..N {
+                    assert_eq!(Some(i * 10 + t), map.remove(&format!("{:04}-{}", i, t)));
+                }
+            }));
+        }
+
+        let scanners: Vec<_> = (0..2)
+            .map(|_| {
+                let map = Arc::clone(&map);
+                thread::spawn(move || {
+                    for _ in 0..300 {
+                        let mut seen = vec![Vec::new(); 3];
+                        for (k, v) in map.range(..) {
+                            let (i, t) = (v / 10, v % 10);
+                            assert_eq!(format!("{:04}-{}", i, t), k);
+                            seen[t].push(i);
+                        }
+                        for s in seen.iter().filter(|s| !s.is_empty()) {
+                            assert!(s.windows(2).all(|w| w[0] + 1 == w[1]), "gap in {:?}", s);
+                            assert!(s[0] == 0 || *s.last().unwrap() == N - 1);
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for h in handles.into_iter().chain(scanners) {
+            h.join().unwrap();
+        }
+        assert!(map.is_empty());
+    }
+}
@@ -0,0 +1,461 @@
+// 带权图：邻接表存储，节点以 String 为 id，内部映射到 0..n 的下标
+use std::collections::{HashMap, VecDeque};
+
+use heap::{DaryHeap, Handle};
+use union_find::DisjointSet;
+
+pub struct Graph {
+    directed: bool,
+    index: HashMap<String, usize>,
+    ids: Vec<String>,
+    // adj[u] 中保存 (v, weight)；无向图的每条边在两端各存一次
+    adj: Vec<Vec<(usize, i64)>>,
+    edges: usize,
+}
+
+// 拓扑排序失败的原因
+#[derive(Debug, PartialEq, Eq)]
+pub enum TopoError {
+    // 无向图没有拓扑序
+    NotDirected,
+    // 环上的节点（按边的方向）
+    Cycle(Vec<String>),
+}
+
+// 单源最短路的结果，distance 为 None 表示不可达
+pub struct ShortestPaths<'a> {
+    graph: &'a Graph,
+    dist: Vec<Option<i64>>,
+    prev: Vec<Option<usize>>,
+}
+
+impl<'a> ShortestPaths<'a> {
+    pub fn distance(&self, id: &str) -> Option<i64> {
+        self.dist[*self.graph.index.get(id)?]
+    }
+
+    // 从起点到 id 的路径（含两端），不可达时为 None
+    pub fn path(&self, id: &str) -> Option<Vec<String>> {
+        let mut v = *self.graph.index.get(id)?;
+        self.dist[v]?;
+        let mut path = vec![self.graph.ids[v].clone()];
+        while let Some(p) = self.prev[v] {
+            path.push(self.graph.ids[p].clone());
+            v = p;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+impl Graph {
+    pub fn directed() -> Self { Self::_new(true) }
+
+    pub fn undirected() -> Self { Self::_new(false) }
+
+    fn _new(directed: bool) -> Self {
+        Self {
+            directed,
+            index: HashMap::new(),
+            ids: Vec::new(),
+            adj: Vec::new(),
+            edges: 0,
+        }
+    }
+
+    pub fn is_directed(&self) -> bool { self.directed }
+
+    pub fn node_count(&self) -> usize { self.ids.len() }
+
+    pub fn edge_count(&self) -> usize { self.edges }
+
+    pub fn contains_node(&self, id: &str) -> bool { self.index.contains_key(id) }
+
+    // 添加节点，已存在时返回 false
+    pub fn add_node(&mut self, id: String) -> bool {
+        if self.index.contains_key(&id) {
+            return false;
+        }
+        self._node(id);
+        true
+    }
+
+    fn _node(&mut self, id: String) -> usize {
+        if let Some(&u) = self.index.get(&id) {
+            return u;
+        }
+        let u = self.ids.len();
+        self.index.insert(id.clone(), u);
+        self.ids.push(id);
+        self.adj.push(Vec::new());
+        u
+    }
+
+    // 添加边，端点不存在时自动创建；允许重边
+    pub fn add_edge(&mut self, from: &str, to: &str, weight: i64) {
+        let u = self._node(from.to_string());
+        let v = self._node(to.to_string());
+        self.adj[u].push((v, weight));
+        if !self.directed && u != v {
+            self.adj[v].push((u, weight));
+        }
+        self.edges += 1;
+    }
+
+    pub fn neighbors(&self, id: &str) -> Vec<(&str, i64)> {
+        match self.index.get(id) {
+            None => Vec::new(),
+            Some(&u) => self.adj[u].iter().map(|&(v, w)| (self.ids[v].as_str(), w)).collect(),
+        }
+    }
+
+    fn _index(&self, id: &str) -> Result<usize, String> {
+        match self.index.get(id) {
+            None => Err(format!("node={} not exists", id)),
+            Some(&u) => Ok(u),
+        }
+    }
+
+    fn _names(&self, nodes: Vec<usize>) -> Vec<String> {
+        nodes.into_iter().map(|u| self.ids[u].clone()).collect()
+    }
+
+    // 广度优先遍历，按访问顺序返回
+    pub fn bfs(&self, start: &str) -> Result<Vec<String>, String> {
+        let s = self._index(start)?;
+        let mut seen = vec![false; self.ids.len()];
+        let mut order = Vec::new();
+        let mut queue = VecDeque::new();
+        seen[s] = true;
+        queue.push_back(s);
+        while let Some(u) = queue.pop_front() {
+            order.push(u);
+            for &(v, _) in self.adj[u].iter() {
+                if !seen[v] {
+                    seen[v] = true;
+                    queue.push_back(v);
+                }
+            }
+        }
+        Ok(self._names(order))
+    }
+
+    // 深度优先遍历（先序），邻居按加边顺序访问
+    pub fn dfs(&self, start: &str) -> Result<Vec<String>, String> {
+        let s = self._index(start)?;
+        let mut seen = vec![false; self.ids.len()];
+        let mut order = Vec::new();
+        let mut stack = vec![s];
+        while let Some(u) = stack.pop() {
+            if seen[u] {
+                continue;
+            }
+            seen[u] = true;
+            order.push(u);
+            for &(v, _) in self.adj[u].iter().rev() {
+                if !seen[v] {
+                    stack.push(v);
+                }
+            }
+        }
+        Ok(self._names(order))
+    }
+
+    // 拓扑排序；存在环时返回环上的节点
+    pub fn topo_sort(&self) -> Result<Vec<String>, TopoError> {
+        if !self.directed {
+            return Err(TopoError::NotDirected);
+        }
+        // 0: 未访问, 1: 在递归栈中, 2: 已完成
+        let mut color = vec![0u8; self.ids.len()];
+        let mut order = Vec::new();
+        for s in 0..self.ids.len() {
+            if color[s] != 0 {
+                continue;
+            }
+            color[s] = 1;
+            let mut call = vec![(s, 0usize)];
+            while let Some(&(u, i)) = call.last() {
+                if i < self.adj[u].len() {
+                    call.last_mut().unwrap().1 += 1;
+                    let v = self.adj[u][i].0;
+                    match color[v] {
+                        0 => {
+                            color[v] = 1;
+                            call.push((v, 0));
+                        }
+                        1 => {
+                            let pos = call.iter().position(|&(x, _)| x == v).unwrap();
+                            return Err(TopoError::Cycle(self._names(call[pos..].iter().map(|&(x, _)| x).collect())));
+                        }
+                        _ => (),
+                    }
+                } else {
+                    color[u] = 2;
+                    order.push(u);
+                    call.pop();
+                }
+            }
+        }
+        order.reverse();
+        Ok(self._names(order))
+    }
+
+    // 非负权单源最短路
+    pub fn dijkstra(&self, start: &str) -> Result<ShortestPaths<'_>, String> {
+        let s = self._index(start)?;
+        if let Some(w) = self.adj.iter().flat_map(|e| e.iter()).map(|&(_, w)| w).find(|&w| w < 0) {
+            return Err(format!("weight={} is negative", w));
+        }
+
+        let n = self.ids.len();
+        let mut dist = vec![None; n];
+        let mut prev = vec![None; n];
+        let mut handles: Vec<Option<Handle>> = vec![None; n];
+        let mut done = vec![false; n];
+        let mut heap = DaryHeap::new();
+        dist[s] = Some(0);
+        handles[s] = Some(heap.push(0, s));
+
+        while let Some((d, u)) = heap.pop() {
+            done[u] = true;
+            for &(v, w) in self.adj[u].iter() {
+                let nd = d + w;
+                if done[v] || dist[v].is_some_and(|old| old <= nd) {
+                    continue;
+                }
+                dist[v] = Some(nd);
+                prev[v] = Some(u);
+                // 已在堆中的节点直接调小优先级
+                match handles[v] {
+                    Some(h) if heap.contains(h) => {
+                        heap.change_priority(h, nd)?;
+                    }
+                    _ => handles[v] = Some(heap.push(nd, v)),
+                }
+            }
+        }
+        Ok(ShortestPaths { graph: self, dist, prev })
+    }
+
+    // 允许负权的单源最短路；从起点可达负环时返回 Err
+    pub fn bellman_ford(&self, start: &str) -> Result<ShortestPaths<'_>, String> {
+        let s = self._index(start)?;
+        let n = self.ids.len();
+        let mut dist: Vec<Option<i64>> = vec![None; n];
+        let mut prev = vec![None; n];
+        dist[s] = Some(0);
+
+        for round in 0..n {
+            let mut changed = false;
+            for u in 0..n {
+                let du = match dist[u] {
+                    None => continue,
+                    Some(d) => d,
+                };
+                for &(v, w) in self.adj[u].iter() {
+                    if dist[v].is_none_or(|old| du + w < old) {
+                        dist[v] = Some(du + w);
+                        prev[v] = Some(u);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+            // 第 n 轮仍能松弛说明存在负环
+            if round == n - 1 {
+                return Err(format!("negative cycle reachable from node={}", start));
+            }
+        }
+        Ok(ShortestPaths { graph: self, dist, prev })
+    }
+
+    // 强连通分量（Tarjan），按逆拓扑序输出；无向图即为连通分量
+    pub fn scc(&self) -> Vec<Vec<String>> {
+        let n = self.ids.len();
+        let mut index = vec![usize::MAX; n];
+        let mut low = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut out = Vec::new();
+        let mut counter = 0;
+
+        for s in 0..n {
+            if index[s] != usize::MAX {
+                continue;
+            }
+            index[s] = counter;
+            low[s] = counter;
+            counter += 1;
+            stack.push(s);
+            on_stack[s] = true;
+            let mut call = vec![(s, 0usize)];
+
+            while let Some(&(u, i)) = call.last() {
+                if i < self.adj[u].len() {
+                    call.last_mut().unwrap().1 += 1;
+                    let v = self.adj[u][i].0;
+                    if index[v] == usize::MAX {
+                        index[v] = counter;
+                        low[v] = counter;
+                        counter += 1;
+                        stack.push(v);
+                        on_stack[v] = true;
+                        call.push((v, 0));
+                    } else if on_stack[v] {
+                        low[u] = low[u].min(index[v]);
+                    }
+                    continue;
+                }
+
+                call.pop();
+                if let Some(&(p, _)) = call.last() {
+                    low[p] = low[p].min(low[u]);
+                }
+                if low[u] == index[u] {
+                    let mut comp = Vec::new();
+                    loop {
+                        let x = stack.pop().unwrap();
+                        on_stack[x] = false;
+                        comp.push(x);
+                        if x == u {
+                            break;
+                        }
+                    }
+                    out.push(self._names(comp));
+                }
+            }
+        }
+        out
+    }
+
+    // 最小生成树（Kruskal），图不连通时得到最小生成森林
+    pub fn mst(&self) -> Result<Vec<(String, String, i64)>, String> {
+        if self.directed {
+            return Err(String::from("mst requires an undirected graph"));
+        }
+        let mut edges = Vec::new();
+        for (u, list) in self.adj.iter().enumerate() {
+            for &(v, w) in list.iter() {
+                if u < v {
+                    edges.push((w, u, v));
+                }
+            }
+        }
+        edges.sort();
+
+        let mut sets = DisjointSet::new(self.ids.len());
+        let mut tree = Vec::new();
+        for (w, u, v) in edges {
+            if sets.union(u, v) {
+                tree.push((self.ids[u].clone(), self.ids[v].clone(), w));
+            }
+        }
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut groups: Vec<Vec<String>>) -> Vec<Vec<String>> {
+        for g in groups.iter_mut() {
+            g.sort();
+        }
+        groups.sort();
+        groups
+    }
+
+    #[test]
+    fn traversal_and_topo_sort() {
+        let mut g = Graph::directed();
+        for &(a, b) in [("shirt", "tie"), ("tie", "jacket"), ("pants", "shoes"), ("pants", "belt"), ("belt", "jacket"), ("shirt", "belt"), ("socks", "shoes")].iter() {
+            g.add_edge(a, b, 1);
+        }
+        assert_eq!(7, g.node_count());
+        assert_eq!(7, g.edge_count());
+        assert_eq!(vec!["shirt", "tie", "belt", "jacket"], g.bfs("shirt").unwrap());
+        assert_eq!(vec!["shirt", "tie", "jacket", "belt"], g.dfs("shirt").unwrap());
+        assert!(g.bfs("hat").is_err());
+
+        let order = g.topo_sort().unwrap();
+        assert_eq!(7, order.len());
+        let pos = |id: &str| order.iter().position(|x| x == id).unwrap();
+        for u in order.iter() {
+            for (v, _) in g.neighbors(u) {
+                assert!(pos(u) < pos(v));
+            }
+        }
+
+        g.add_edge("jacket", "pants", 1);
+        g.add_edge("belt", "shirt", 1);
+        let cycle = match g.topo_sort() {
+            Err(TopoError::Cycle(cycle)) => cycle,
+            other => panic!("expect a cycle, got {:?}", other),
+        };
+        for (i, u) in cycle.iter().enumerate() {
+            let v = &cycle[(i + 1) % cycle.len()];
+            assert!(g.neighbors(u).iter().any(|&(x, _)| x == v));
+        }
+    }
+
+    #[test]
+    fn shortest_paths() {
+        let mut g = Graph::directed();
+        for &(a, b, w) in [("s", "a", 4), ("s", "b", 1), ("b", "a", 2), ("a", "c", 1), ("b", "c", 5), ("c", "d", 3)].iter() {
+            g.add_edge(a, b, w);
+        }
+        g.add_node(String::from("island"));
+
+        for paths in [g.dijkstra("s").unwrap(), g.bellman_ford("s").unwrap()].iter() {
+            assert_eq!(Some(3), paths.distance("a"));
+            assert_eq!(Some(7), paths.distance("d"));
+            assert_eq!(None, paths.distance("island"));
+            assert_eq!(Some(vec!["s", "b", "a", "c", "d"].into_iter().map(String::from).collect()), paths.path("d"));
+            assert_eq!(None, paths.path("island"));
+        }
+
+        // 负权边：Dijkstra 拒绝，Bellman-Ford 正常
+        g.add_edge("s", "c", -1);
+        assert!(g.dijkstra("s").is_err());
+        let paths = g.bellman_ford("s").unwrap();
+        assert_eq!(Some(2), paths.distance("d"));
+
+        g.add_edge("d", "s", -3);
+        assert!(g.bellman_ford("s").is_err());
+        assert!(g.bellman_ford("island").is_ok());
+    }
+
+    #[test]
+    fn components_and_mst() {
+        let mut g = Graph::directed();
+        for &(a, b) in [("a", "b"), ("b", "c"), ("c", "a"), ("b", "d"), ("d", "e"), ("e", "d"), ("f", "f")].iter() {
+            g.add_edge(a, b, 1);
+        }
+        let comps = g.scc();
+        // Tarjan 按逆拓扑序输出：{d,e} 在 {a,b,c} 之前
+        let de = comps.iter().position(|c| c.len() == 2).unwrap();
+        let abc = comps.iter().position(|c| c.len() == 3).unwrap();
+        assert!(de < abc);
+        let expect: Vec<Vec<String>> = vec![vec!["a", "b", "c"], vec!["d", "e"], vec!["f"]]
+            .into_iter()
+            .map(|c| c.into_iter().map(String::from).collect())
+            .collect();
+        assert_eq!(expect, sorted(comps));
+        assert!(g.mst().is_err());
+
+        let mut g = Graph::undirected();
+        for &(a, b, w) in [("a", "b", 7), ("a", "d", 5), ("b", "c", 8), ("b", "d", 9), ("b", "e", 7), ("c", "e", 5), ("d", "e", 15), ("d", "f", 6), ("e", "f", 8), ("e", "g", 9), ("f", "g", 11), ("x", "y", 1)].iter() {
+            g.add_edge(a, b, w);
+        }
+        assert_eq!(Err(TopoError::NotDirected), g.topo_sort());
+        let tree = g.mst().unwrap();
+        assert_eq!(7, tree.len());
+        assert_eq!(40, tree.iter().map(|&(_, _, w)| w).sum::<i64>());
+        assert_eq!(2, g.scc().len());
+        assert_eq!(vec!["b", "a", "c", "d", "e"], g.bfs("b").unwrap()[..5].to_vec());
+    }
+}
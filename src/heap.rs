@@ -0,0 +1,241 @@
+// 带句柄的 d 叉最小堆：push 返回句柄，之后可通过句柄修改优先级或删除元素
+const DEFAULT_ARITY: usize = 4;
+
+// 句柄在元素出堆后失效；槽位被复用时 gen 会变化，旧句柄不会误指新元素
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Handle {
+    id: usize,
+    gen: u32,
+}
+
+struct Slot {
+    pos: Option<usize>,
+    gen: u32,
+}
+
+struct Entry<P, T> {
+    priority: P,
+    item: T,
+    id: usize,
+}
+
+pub struct DaryHeap<P: Ord, T> {
+    arity: usize,
+    heap: Vec<Entry<P, T>>,
+    slots: Vec<Slot>,
+    free: Vec<usize>,
+}
+
+impl<P: Ord, T> Default for DaryHeap<P, T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<P: Ord, T> DaryHeap<P, T> {
+    pub fn new() -> Self { Self::with_arity(DEFAULT_ARITY) }
+
+    pub fn with_arity(arity: usize) -> Self {
+        assert!(arity >= 2, "arity={} is too small, at least 2", arity);
+        Self {
+            arity,
+            heap: Vec::new(),
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize { self.heap.len() }
+
+    pub fn is_empty(&self) -> bool { self.heap.is_empty() }
+
+    pub fn push(&mut self, priority: P, item: T) -> Handle {
+        let pos = self.heap.len();
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.slots[id].pos = Some(pos);
+                id
+            }
+            None => {
+                self.slots.push(Slot { pos: Some(pos), gen: 0 });
+                self.slots.len() - 1
+            }
+        };
+        self.heap.push(Entry { priority, item, id });
+        self._sift_up(pos);
+        Handle { id, gen: self.slots[id].gen }
+    }
+
+    pub fn peek(&self) -> Option<(&P, &T)> {
+        self.heap.first().map(|e| (&e.priority, &e.item))
+    }
+
+    pub fn pop(&mut self) -> Option<(P, T)> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        Some(self._remove_at(0))
+    }
+
+    pub fn contains(&self, handle: Handle) -> bool { self._pos(handle).is_some() }
+
+    pub fn get(&self, handle: Handle) -> Option<(&P, &T)> {
+        self._pos(handle).map(|pos| {
+            let e = &self.heap[pos];
+            (&e.priority, &e.item)
+        })
+    }
+
+    // 修改优先级（可增可减），返回原优先级
+    pub fn change_priority(&mut self, handle: Handle, priority: P) -> Result<P, String> {
+        let pos = match self._pos(handle) {
+            None => return Err(format!("handle={:?} not exists", handle)),
+            Some(pos) => pos,
+        };
+        let old = ::std::mem::replace(&mut self.heap[pos].priority, priority);
+        if self.heap[pos].priority < old {
+            self._sift_up(pos);
+        } else {
+            self._sift_down(pos);
+        }
+        Ok(old)
+    }
+
+    pub fn remove(&mut self, handle: Handle) -> Option<(P, T)> {
+        let pos = self._pos(handle)?;
+        Some(self._remove_at(pos))
+    }
+
+    fn _pos(&self, handle: Handle) -> Option<usize> {
+        match self.slots.get(handle.id) {
+            Some(slot) if slot.gen == handle.gen => slot.pos,
+            _ => None,
+        }
+    }
+
+    // 用最后一个元素填补 pos，再根据大小向上或向下调整
+    fn _remove_at(&mut self, pos: usize) -> (P, T) {
+        let last = self.heap.len() - 1;
+        self._swap(pos, last);
+        let e = self.heap.pop().unwrap();
+
+        let slot = &mut self.slots[e.id];
+        slot.pos = None;
+        slot.gen = slot.gen.wrapping_add(1);
+        self.free.push(e.id);
+
+        if pos < self.heap.len() {
+            self._sift_up(pos);
+            self._sift_down(pos);
+        }
+        (e.priority, e.item)
+    }
+
+    fn _swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.slots[self.heap[a].id].pos = Some(a);
+        self.slots[self.heap[b].id].pos = Some(b);
+    }
+
+    fn _sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / self.arity;
+            if self.heap[pos].priority >= self.heap[parent].priority {
+                break;
+            }
+            self._swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    fn _sift_down(&mut self, mut pos: usize) {
+        loop {
+            let first = pos * self.arity + 1;
+            if first >= self.heap.len() {
+                break;
+            }
+            let end = (first + self.arity).min(self.heap.len());
+            let mut min = first;
+            for c in first + 1..end {
+                if self.heap[c].priority < self.heap[min].priority {
+                    min = c;
+                }
+            }
+            if self.heap[min].priority >= self.heap[pos].priority {
+                break;
+            }
+            self._swap(pos, min);
+            pos = min;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::Lcg;
+
+    fn check<P: Ord, T>(heap: &DaryHeap<P, T>) {
+        for i in 1..heap.heap.len() {
+            assert!(heap.heap[(i - 1) / heap.arity].priority <= heap.heap[i].priority);
+        }
+        for (i, e) in heap.heap.iter().enumerate() {
+            assert_eq!(Some(i), heap.slots[e.id].pos);
+        }
+    }
+
+    #[test]
+    fn push_pop_sorted() {
+        for arity in 2..6 {
+            let mut heap = DaryHeap::with_arity(arity);
+            let mut rng = Lcg::new(13);
+            let mut expect = Vec::new();
+            for i in 0..300 {
+                let seed = rng.next_u32();
+                let p = (seed >> 16) % 1000;
+                heap.push(p, i);
+                expect.push(p);
+            }
+            check(&heap);
+            expect.sort();
+            assert_eq!(Some(&expect[0]), heap.peek().map(|(p, _)| p));
+
+            let mut actual = Vec::new();
+            while let Some((p, _)) = heap.pop() {
+                actual.push(p);
+            }
+            assert_eq!(expect, actual);
+            assert!(heap.is_empty());
+        }
+    }
+
+    #[test]
+    fn change_priority_and_remove() {
+        let mut heap = DaryHeap::new();
+        let handles: Vec<Handle> = (0..50).map(|i| heap.push(100 + i, format!("task{}", i))).collect();
+
+        assert_eq!(Ok(140), heap.change_priority(handles[40], 1));
+        assert_eq!(Some((&1, &String::from("task40"))), heap.peek());
+        assert_eq!(Ok(100), heap.change_priority(handles[0], 500));
+        check(&heap);
+        assert_eq!(Some((110, String::from("task10"))), heap.remove(handles[10]));
+        assert_eq!(None, heap.remove(handles[10]));
+        assert!(heap.change_priority(handles[10], 0).is_err());
+        check(&heap);
+
+        assert_eq!(Some((1, String::from("task40"))), heap.pop());
+        assert_eq!(Some((101, String::from("task1"))), heap.pop());
+        assert!(!heap.contains(handles[1]));
+
+        // 槽位被复用后旧句柄依然无效
+        let h = heap.push(0, String::from("new"));
+        assert!(!heap.contains(handles[1]));
+        assert_eq!(Some((&0, &String::from("new"))), heap.get(h));
+        assert_eq!(48, heap.len());
+
+        let mut last = 0;
+        while let Some((p, _)) = heap.pop() {
+            assert!(p >= last);
+            last = p;
+        }
+        assert_eq!(500, last);
+    }
+}
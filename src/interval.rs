@@ -0,0 +1,214 @@
+// 区间树：按区间 (start, end) 排序的 AVL 树，子树摘要为其中最大的右端点
+use std::cell::RefCell;
+use std::fmt;
+use std::fmt::Display;
+use std::rc::Rc;
+
+use aggregate::Monoid;
+use tree::avl::AVLTree;
+use tree::binary::{BinaryNode, Summary};
+use tree::search::SearchTree;
+
+const SIGN: u64 = 1 << 63;
+
+// 闭区间 [start, end]
+#[derive(Clone, Debug)]
+pub struct Interval<V> {
+    start: i64,
+    end: i64,
+    value: V,
+}
+
+impl<V> Interval<V> {
+    pub fn start(&self) -> i64 { self.start }
+
+    pub fn end(&self) -> i64 { self.end }
+
+    pub fn value(&self) -> &V { &self.value }
+
+    fn overlaps(&self, lo: i64, hi: i64) -> bool {
+        self.start <= hi && lo <= self.end
+    }
+}
+
+impl<V: Display> Display for Interval<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {}] {}", self.start, self.end, self.value)
+    }
+}
+
+// 编码成定长十六进制，翻转符号位后字符串序与数值序一致
+fn _encode_key(start: i64, end: i64) -> String {
+    format!("{:016x}{:016x}", (start as u64) ^ SIGN, (end as u64) ^ SIGN)
+}
+
+// 子树中最大的右端点，空子树为 i64::MIN
+#[derive(Clone, Debug)]
+pub struct MaxEnd;
+
+impl Monoid for MaxEnd {
+    type Item = i64;
+
+    fn identity() -> i64 { i64::MIN }
+    fn combine(a: &i64, b: &i64) -> i64 { *a.max(b) }
+}
+
+impl<V> Summary<Interval<V>> for MaxEnd {
+    fn measure(interval: &Interval<V>) -> i64 { interval.end }
+}
+
+type NodeRc<V> = Rc<RefCell<BinaryNode<Interval<V>, MaxEnd>>>;
+
+fn _overlap<V: Clone + Display>(
+    node: &NodeRc<V>,
+    lo: i64,
+    hi: i64,
+    out: &mut Vec<Interval<V>>,
+) {
+    let borrow = node.as_ref().borrow();
+    let interval = borrow.value();
+
+    // 左子树中最大的右端点都小于 lo 时，整棵左子树都不可能重叠
+    if let Some(ref left) = *borrow.left() {
+        if *left.as_ref().borrow().summary() >= lo {
+            _overlap(left, lo, hi, out);
+        }
+    }
+    if interval.overlaps(lo, hi) {
+        out.push(interval.clone());
+    }
+    // 右子树的起点都不小于当前节点的起点
+    if interval.start <= hi {
+        if let Some(ref right) = *borrow.right() {
+            _overlap(right, lo, hi, out);
+        }
+    }
+}
+
+pub struct IntervalTree<V: Clone + Display> {
+    tree: AVLTree<Interval<V>, MaxEnd>,
+    len: usize,
+}
+
+impl<V: Clone + Display> Default for IntervalTree<V> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<V: Clone + Display> IntervalTree<V> {
+    pub fn new() -> Self {
+        Self { tree: AVLTree::new(), len: 0 }
+    }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    pub fn height(&self) -> i32 { self.tree.height() }
+
+    pub fn add(&mut self, start: i64, end: i64, value: V) -> Result<(), String> {
+        if start > end {
+            return Err(format!("interval=[{}, {}] is invalid", start, end));
+        }
+        let interval = Interval { start, end, value };
+        self.tree.add(_encode_key(start, end), interval);
+        self.len += 1;
+        Ok(())
+    }
+
+    // 删除一个端点完全相同的区间，返回它的值
+    pub fn remove(&mut self, start: i64, end: i64) -> Option<V> {
+        let interval = self.tree.remove(&_encode_key(start, end))?;
+        self.len -= 1;
+        Some(interval.value)
+    }
+
+    // 与 [lo, hi] 有交集的所有区间，按 (start, end) 升序
+    pub fn overlap(&self, lo: i64, hi: i64) -> Vec<Interval<V>> {
+        let mut out = Vec::new();
+        if let Some(ref root) = *self.tree.root() {
+            _overlap(root, lo, hi, &mut out);
+        }
+        out
+    }
+
+    // 包含 point 的所有区间
+    pub fn stab(&self, point: i64) -> Vec<Interval<V>> {
+        self.overlap(point, point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::Lcg;
+
+    // 校验每个节点缓存的最大右端点并返回它
+    fn check<V: Clone + Display>(node: &Option<NodeRc<V>>) -> i64 {
+        match *node {
+            None => i64::MIN,
+            Some(ref rc) => {
+                let borrow = rc.as_ref().borrow();
+                let left = check(borrow.left());
+                let right = check(borrow.right());
+                let end = borrow.value().end;
+                assert_eq!(end.max(left).max(right), *borrow.summary());
+                *borrow.summary()
+            }
+        }
+    }
+
+    fn spans<V>(intervals: &[Interval<V>]) -> Vec<(i64, i64)> {
+        intervals.iter().map(|i| (i.start(), i.end())).collect()
+    }
+
+    #[test]
+    fn overlap_and_stab() {
+        let mut tree = IntervalTree::new();
+        for &(s, e) in [(15, 20), (10, 30), (17, 19), (5, 20), (12, 15), (30, 40), (-5, 3)].iter() {
+            tree.add(s, e, format!("{}-{}", s, e)).unwrap();
+            check(tree.tree.root());
+        }
+        assert!(tree.add(3, 2, String::new()).is_err());
+        assert_eq!(7, tree.len());
+
+        assert_eq!(vec![(5, 20), (10, 30), (12, 15), (15, 20)], spans(&tree.overlap(14, 16)));
+        assert_eq!(vec![(10, 30), (30, 40)], spans(&tree.stab(30)));
+        assert_eq!(vec![(-5, 3)], spans(&tree.stab(0)));
+        assert!(tree.overlap(41, 100).is_empty());
+        assert_eq!("17-19", tree.stab(18)[3].value());
+
+        assert_eq!(Some(String::from("10-30")), tree.remove(10, 30));
+        assert_eq!(None, tree.remove(10, 30));
+        check(tree.tree.root());
+        assert_eq!(vec![(30, 40)], spans(&tree.stab(30)));
+    }
+
+    #[test]
+    fn random_against_brute_force() {
+        let mut tree = IntervalTree::new();
+        let mut all: Vec<(i64, i64)> = Vec::new();
+        let mut rng = Lcg::new(5);
+        let mut next = move || i64::from(rng.next_u32() >> 16);
+
+        for i in 0..300 {
+            let start = next() % 1000 - 500;
+            let end = start + next() % 100;
+            tree.add(start, end, i).unwrap();
+            all.push((start, end));
+        }
+        for _ in 0..100 {
+            let (s, e) = all.remove((next() as usize) % all.len());
+            assert!(tree.remove(s, e).is_some());
+        }
+        assert_eq!(check(tree.tree.root()), tree.tree.summary());
+        assert_eq!(all.len(), tree.len());
+
+        all.sort();
+        for _ in 0..100 {
+            let lo = next() % 1200 - 600;
+            let hi = lo + next() % 50;
+            let expect: Vec<(i64, i64)> = all.iter().cloned().filter(|&(s, e)| s <= hi && lo <= e).collect();
+            assert_eq!(expect, spans(&tree.overlap(lo, hi)));
+        }
+    }
+}
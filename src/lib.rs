@@ -0,0 +1,20 @@
+#[macro_use]
+extern crate getset;
+
+pub mod tree;
+pub mod btree;
+pub mod skiplist;
+pub mod trie;
+pub mod interval;
+pub mod aggregate;
+pub mod heap;
+pub mod meldable;
+pub mod union_find;
+pub mod graph;
+pub mod robin_hood;
+pub mod cache;
+pub mod linked_list;
+pub mod concurrent;
+
+#[cfg(test)]
+mod testing;
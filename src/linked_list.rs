@@ -0,0 +1,520 @@
+// 双向链表：与 BinaryNode 相同，Rc 指向后继、Weak 指回前驱，因此不会形成引用环
+use std::cell::{Ref, RefCell, RefMut};
+use std::mem;
+use std::rc::{Rc, Weak};
+
+type NodeRc<T> = Rc<RefCell<ListNode<T>>>;
+
+struct ListNode<T> {
+    value: T,
+    prev: Option<Weak<RefCell<ListNode<T>>>>,
+    next: Option<NodeRc<T>>,
+}
+
+pub struct LinkedList<T> {
+    head: Option<NodeRc<T>>,
+    tail: Option<Weak<RefCell<ListNode<T>>>>,
+    len: usize,
+}
+
+fn _new_node<T>(value: T) -> NodeRc<T> {
+    Rc::new(RefCell::new(ListNode { value, prev: None, next: None }))
+}
+
+fn _into_value<T>(node: NodeRc<T>) -> T {
+    match Rc::try_unwrap(node) {
+        Ok(cell) => cell.into_inner().value,
+        Err(_) => unreachable!("unlinked node is still referenced"),
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T> LinkedList<T> {
+    pub fn new() -> Self { Self { head: None, tail: None, len: 0 } }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    fn _tail(&self) -> Option<NodeRc<T>> { self.tail.as_ref().and_then(|w| w.upgrade()) }
+
+    pub fn front(&self) -> Option<Ref<'_, T>> {
+        self.head.as_ref().map(|n| Ref::map(n.as_ref().borrow(), |n| &n.value))
+    }
+
+    pub fn front_mut(&mut self) -> Option<RefMut<'_, T>> {
+        self.head.as_ref().map(|n| RefMut::map(n.as_ref().borrow_mut(), |n| &mut n.value))
+    }
+
+    // 尾指针是 Weak，返回的 Ref 需要借助临时的 Rc，因此只提供克隆
+    pub fn back_clone(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self._tail().map(|n| n.as_ref().borrow().value.clone())
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        let node = _new_node(value);
+        let next = self.head.clone();
+        self._splice(None, next, Rc::clone(&node), node, 1);
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        let node = _new_node(value);
+        let prev = self._tail();
+        self._splice(prev, None, Rc::clone(&node), node, 1);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.head.clone()?;
+        self._unlink(&head);
+        Some(_into_value(head))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self._tail()?;
+        self._unlink(&tail);
+        Some(_into_value(tail))
+    }
+
+    // 把 other 整体接到尾部，O(1)
+    pub fn append(&mut self, other: &mut LinkedList<T>) {
+        let mut cursor = self.cursor_back_mut();
+        cursor.splice_after(mem::take(other));
+    }
+
+    // 游标从头部开始；空链表时指向"幽灵"位置
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        let cur = self.head.clone();
+        CursorMut { list: self, cur, index: 0 }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        let cur = self._tail();
+        let index = self.len.saturating_sub(1);
+        CursorMut { list: self, cur, index }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { front: self.head.clone(), back: self._tail(), remaining: self.len, _list: self }
+    }
+
+    // 把 first..last 这一段接在相邻的 prev 与 next 之间
+    fn _splice(&mut self, prev: Option<NodeRc<T>>, next: Option<NodeRc<T>>, first: NodeRc<T>, last: NodeRc<T>, count: usize) {
+        first.as_ref().borrow_mut().prev = prev.as_ref().map(Rc::downgrade);
+        match next {
+            Some(ref n) => n.as_ref().borrow_mut().prev = Some(Rc::downgrade(&last)),
+            None => self.tail = Some(Rc::downgrade(&last)),
+        }
+        last.as_ref().borrow_mut().next = next;
+        match prev {
+            Some(p) => p.as_ref().borrow_mut().next = Some(first),
+            None => self.head = Some(first),
+        }
+        self.len += count;
+    }
+
+    fn _unlink(&mut self, node: &NodeRc<T>) {
+        let prev = node.as_ref().borrow_mut().prev.take().and_then(|w| w.upgrade());
+        let next = node.as_ref().borrow_mut().next.take();
+        match next {
+            Some(ref n) => n.as_ref().borrow_mut().prev = prev.as_ref().map(Rc::downgrade),
+            None => self.tail = prev.as_ref().map(Rc::downgrade),
+        }
+        match prev {
+            Some(ref p) => p.as_ref().borrow_mut().next = next,
+            None => self.head = next,
+        }
+        self.len -= 1;
+    }
+
+    // 从 node 处断开，node 及其后的 count 个元素组成新链表返回
+    fn _split_before(&mut self, node: NodeRc<T>, count: usize) -> LinkedList<T> {
+        let tail = self.tail.take();
+        match node.as_ref().borrow_mut().prev.take().and_then(|w| w.upgrade()) {
+            Some(p) => {
+                p.as_ref().borrow_mut().next = None;
+                self.tail = Some(Rc::downgrade(&p));
+            }
+            None => self.head = None,
+        }
+        self.len -= count;
+        LinkedList { head: Some(node), tail, len: count }
+    }
+}
+
+impl<T: Clone> LinkedList<T> {
+    pub fn to_vec(&self) -> Vec<T> { self.iter().collect() }
+}
+
+// 逐个断开后继，避免长链表递归析构
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        let mut cur = self.head.take();
+        while let Some(n) = cur {
+            cur = n.as_ref().borrow_mut().next.take();
+        }
+    }
+}
+
+// 可修改的游标，指向某个元素或首尾之间的"幽灵"位置
+pub struct CursorMut<'a, T: 'a> {
+    list: &'a mut LinkedList<T>,
+    cur: Option<NodeRc<T>>,
+    index: usize,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    // 幽灵位置时为 None
+    pub fn index(&self) -> Option<usize> { self.cur.as_ref().map(|_| self.index) }
+
+    pub fn current(&self) -> Option<Ref<'_, T>> {
+        self.cur.as_ref().map(|n| Ref::map(n.as_ref().borrow(), |n| &n.value))
+    }
+
+    pub fn current_mut(&mut self) -> Option<RefMut<'_, T>> {
+        self.cur.as_ref().map(|n| RefMut::map(n.as_ref().borrow_mut(), |n| &mut n.value))
+    }
+
+    // 走到尾部之后进入幽灵位置，再走回到头部
+    pub fn move_next(&mut self) {
+        match self.cur.take() {
+            None => {
+                self.cur = self.list.head.clone();
+                self.index = 0;
+            }
+            Some(n) => {
+                self.cur = n.as_ref().borrow().next.clone();
+                self.index += 1;
+            }
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        match self.cur.take() {
+            None => {
+                self.cur = self.list._tail();
+                self.index = self.list.len.saturating_sub(1);
+            }
+            Some(n) => {
+                self.cur = n.as_ref().borrow().prev.as_ref().and_then(|w| w.upgrade());
+                self.index = self.index.wrapping_sub(1);
+            }
+        }
+    }
+
+    // 在当前元素之后插入；幽灵位置时插到头部
+    pub fn insert_after(&mut self, value: T) {
+        let node = _new_node(value);
+        match self.cur {
+            None => {
+                let next = self.list.head.clone();
+                self.list._splice(None, next, Rc::clone(&node), node, 1);
+            }
+            Some(ref c) => {
+                let next = c.as_ref().borrow().next.clone();
+                self.list._splice(Some(Rc::clone(c)), next, Rc::clone(&node), node, 1);
+            }
+        }
+    }
+
+    // 在当前元素之前插入；幽灵位置时插到尾部
+    pub fn insert_before(&mut self, value: T) {
+        let node = _new_node(value);
+        match self.cur {
+            None => {
+                let prev = self.list._tail();
+                self.list._splice(prev, None, Rc::clone(&node), node, 1);
+            }
+            Some(ref c) => {
+                let prev = c.as_ref().borrow().prev.as_ref().and_then(|w| w.upgrade());
+                self.list._splice(prev, Some(Rc::clone(c)), Rc::clone(&node), node, 1);
+                self.index += 1;
+            }
+        }
+    }
+
+    // 删除当前元素，游标移到下一个元素
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.cur.take()?;
+        self.cur = node.as_ref().borrow().next.clone();
+        self.list._unlink(&node);
+        Some(_into_value(node))
+    }
+
+    // 当前元素之后的部分拆成新链表；幽灵位置时拆走全部
+    pub fn split_after(&mut self) -> LinkedList<T> {
+        let next = match self.cur {
+            None => return mem::take(self.list),
+            Some(ref c) => c.as_ref().borrow().next.clone(),
+        };
+        match next {
+            None => LinkedList::new(),
+            Some(n) => {
+                let count = self.list.len - self.index - 1;
+                self.list._split_before(n, count)
+            }
+        }
+    }
+
+    // 当前元素之前的部分拆成新链表；幽灵位置时拆走全部
+    pub fn split_before(&mut self) -> LinkedList<T> {
+        let cur = match self.cur {
+            None => return mem::take(self.list),
+            Some(ref c) => Rc::clone(c),
+        };
+        if self.index == 0 {
+            return LinkedList::new();
+        }
+        let count = self.list.len - self.index;
+        let rest = self.list._split_before(cur, count);
+        self.index = 0;
+        mem::replace(self.list, rest)
+    }
+
+    // 把 other 整体接在当前元素之后；幽灵位置时接到头部
+    pub fn splice_after(&mut self, mut other: LinkedList<T>) {
+        let (first, last) = match (other.head.take(), other._tail()) {
+            (Some(f), Some(l)) => (f, l),
+            _ => return,
+        };
+        let count = mem::replace(&mut other.len, 0);
+        other.tail = None;
+        let (prev, next) = match self.cur {
+            None => (None, self.list.head.clone()),
+            Some(ref c) => (Some(Rc::clone(c)), c.as_ref().borrow().next.clone()),
+        };
+        self.list._splice(prev, next, first, last, count);
+    }
+
+    // 把 other 整体接在当前元素之前；幽灵位置时接到尾部
+    pub fn splice_before(&mut self, mut other: LinkedList<T>) {
+        let (first, last) = match (other.head.take(), other._tail()) {
+            (Some(f), Some(l)) => (f, l),
+            _ => return,
+        };
+        let count = mem::replace(&mut other.len, 0);
+        other.tail = None;
+        let (prev, next) = match self.cur {
+            None => (self.list._tail(), None),
+            Some(ref c) => {
+                self.index += count;
+                (c.as_ref().borrow().prev.as_ref().and_then(|w| w.upgrade()), Some(Rc::clone(c)))
+            }
+        };
+        self.list._splice(prev, next, first, last, count);
+    }
+}
+
+// 节点藏在 RefCell 里，借用无法跨节点延续，因此迭代时克隆元素
+pub struct Iter<'a, T: 'a> {
+    front: Option<NodeRc<T>>,
+    back: Option<NodeRc<T>>,
+    remaining: usize,
+    _list: &'a LinkedList<T>,
+}
+
+impl<'a, T: Clone> Iterator for Iter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.front.take()?;
+        self.remaining -= 1;
+        let b = node.as_ref().borrow();
+        self.front = b.next.clone();
+        Some(b.value.clone())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) { (self.remaining, Some(self.remaining)) }
+}
+
+impl<'a, T: Clone> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.back.take()?;
+        self.remaining -= 1;
+        let b = node.as_ref().borrow();
+        self.back = b.prev.as_ref().and_then(|w| w.upgrade());
+        Some(b.value.clone())
+    }
+}
+
+pub struct IntoIter<T>(LinkedList<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> { self.0.pop_front() }
+
+    fn size_hint(&self) -> (usize, Option<usize>) { (self.0.len, Some(self.0.len)) }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> { self.0.pop_back() }
+}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> { IntoIter(self) }
+}
+
+impl<T> ::std::iter::FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = LinkedList::new();
+        for v in iter {
+            list.push_back(v);
+        }
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 校验前后指针一致、长度正确，并返回所有节点的 Weak 以便检查泄漏
+    fn check<T>(list: &LinkedList<T>) -> Vec<Weak<RefCell<ListNode<T>>>> {
+        let mut nodes = Vec::new();
+        let mut prev: Option<NodeRc<T>> = None;
+        let mut cur = list.head.clone();
+        while let Some(n) = cur {
+            let p = n.as_ref().borrow().prev.as_ref().and_then(|w| w.upgrade());
+            assert_eq!(prev.is_some(), p.is_some());
+            if let (Some(a), Some(b)) = (prev.as_ref(), p.as_ref()) {
+                assert!(Rc::ptr_eq(a, b));
+            }
+            // 除链表中的前驱外没有其它强引用
+            assert_eq!(2, Rc::strong_count(&n));
+            nodes.push(Rc::downgrade(&n));
+            cur = n.as_ref().borrow().next.clone();
+            prev = Some(n);
+        }
+        match (prev, list._tail()) {
+            (None, None) => (),
+            (Some(a), Some(b)) => assert!(Rc::ptr_eq(&a, &b)),
+            _ => panic!("tail mismatch"),
+        }
+        assert_eq!(list.len(), nodes.len());
+        nodes
+    }
+
+    #[test]
+    fn push_pop_and_iter() {
+        let mut list = LinkedList::new();
+        for i in 0..5 {
+            list.push_back(i);
+            list.push_front(-i);
+        }
+        check(&list);
+        assert_eq!(vec![-4, -3, -2, -1, 0, 0, 1, 2, 3, 4], list.to_vec());
+        assert_eq!(vec![4, 3, 2, 1, 0], list.iter().rev().take(5).collect::<Vec<_>>());
+        assert_eq!(Some(-4), list.pop_front());
+        assert_eq!(Some(4), list.pop_back());
+        *list.front_mut().unwrap() = 100;
+        assert_eq!(100, *list.front().unwrap());
+        assert_eq!(Some(3), list.back_clone());
+
+        let mut other: LinkedList<i32> = (10..13).collect();
+        list.append(&mut other);
+        assert!(other.is_empty());
+        check(&list);
+        assert_eq!(vec![12, 11, 10, 3], list.into_iter().rev().take(4).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn cursor_edit_split_splice() {
+        let mut list: LinkedList<i32> = (0..6).collect();
+        {
+            let mut c = list.cursor_front_mut();
+            c.move_next();
+            c.move_next();
+            assert_eq!(Some(2), c.index());
+            assert_eq!(Some(2), c.remove_current());
+            assert_eq!(3, *c.current().unwrap());
+            c.insert_before(20);
+            c.insert_after(30);
+            assert_eq!(Some(3), c.index());
+            *c.current_mut().unwrap() *= 10;
+        }
+        assert_eq!(vec![0, 1, 20, 30, 30, 4, 5], list.to_vec());
+        check(&list);
+
+        // 幽灵位置
+        {
+            let mut c = list.cursor_back_mut();
+            c.move_next();
+            assert_eq!(None, c.index());
+            c.insert_after(-1);
+            c.insert_before(99);
+            c.move_prev();
+            assert_eq!(Some(99), c.current().map(|v| *v));
+            assert_eq!(Some(8), c.index());
+        }
+        assert_eq!(vec![-1, 0, 1, 20, 30, 30, 4, 5, 99], list.to_vec());
+
+        let (front, back) = {
+            let mut c = list.cursor_front_mut();
+            for _ in 0..3 {
+                c.move_next();
+            }
+            let back = c.split_after();
+            let front = c.split_before();
+            assert_eq!(Some(0), c.index());
+            (front, back)
+        };
+        assert_eq!(vec![20], list.to_vec());
+        assert_eq!(vec![-1, 0, 1], front.to_vec());
+        assert_eq!(vec![30, 30, 4, 5, 99], back.to_vec());
+        check(&list);
+        check(&front);
+        check(&back);
+
+        {
+            let mut c = list.cursor_front_mut();
+            c.splice_before(front);
+            c.splice_after(back);
+            assert_eq!(Some(3), c.index());
+            c.splice_after(LinkedList::new());
+        }
+        assert_eq!(vec![-1, 0, 1, 20, 30, 30, 4, 5, 99], list.to_vec());
+        check(&list);
+    }
+
+    #[test]
+    fn no_rc_leak() {
+        let token = Rc::new(());
+        let mut weak = Vec::new();
+        {
+            let mut list: LinkedList<Rc<()>> = (0..1000).map(|_| Rc::clone(&token)).collect();
+            let tail = {
+                let mut c = list.cursor_front_mut();
+                for _ in 0..400 {
+                    c.move_next();
+                }
+                c.remove_current();
+                c.insert_before(Rc::clone(&token));
+                c.split_after()
+            };
+            list.pop_back();
+            weak.extend(check(&list));
+            weak.extend(check(&tail));
+            let mut c = list.cursor_back_mut();
+            c.splice_after(tail);
+            assert_eq!(1000 - 1, list.len());
+            assert_eq!(1000, Rc::strong_count(&token));
+        }
+        assert_eq!(1, Rc::strong_count(&token));
+        assert!(weak.iter().all(|w| w.upgrade().is_none()));
+    }
+}
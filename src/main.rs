@@ -1,9 +1,5 @@
-#[macro_use]
-extern crate getset;
-
-mod tree;
-use tree::*;
+extern crate rust_study;
 
 fn main() {
     println!("test!");
-}
\ No newline at end of file
+}
@@ -0,0 +1,557 @@
+// 可合并堆：配对堆与斐波那契堆，节点用 Rc 持有孩子/后继、Weak 指回前驱，合并只需 O(1) 地接上链表
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+// 两种堆共用的接口，便于同一套测试和基准对两者运行
+pub trait MeldableHeap<P: Ord + Clone, T> {
+    type Handle: Clone;
+
+    fn len(&self) -> usize;
+    fn push(&mut self, priority: P, item: T) -> Self::Handle;
+    fn min_priority(&self) -> Option<P>;
+    fn pop_min(&mut self) -> Option<(P, T)>;
+    // 只允许把优先级调小；元素已出堆时返回 Err
+    fn decrease_key(&mut self, handle: &Self::Handle, priority: P) -> Result<(), String>;
+    fn meld(&mut self, other: Self);
+
+    fn is_empty(&self) -> bool { self.len() == 0 }
+}
+
+// ---------- 配对堆 ----------
+
+type PairingRc<P, T> = Rc<RefCell<PairingNode<P, T>>>;
+
+struct PairingNode<P, T> {
+    priority: P,
+    item: T,
+    child: Option<PairingRc<P, T>>,
+    next: Option<PairingRc<P, T>>,
+    // 前一个兄弟；第一个孩子指向父节点
+    prev: Option<Weak<RefCell<PairingNode<P, T>>>>,
+}
+
+pub struct PairingHandle<P, T>(Weak<RefCell<PairingNode<P, T>>>);
+
+impl<P, T> Clone for PairingHandle<P, T> {
+    fn clone(&self) -> Self { PairingHandle(Weak::clone(&self.0)) }
+}
+
+pub struct PairingHeap<P: Ord + Clone, T> {
+    root: Option<PairingRc<P, T>>,
+    len: usize,
+}
+
+// 较小的根成为新根，另一个作为它的第一个孩子
+fn _pairing_link<P: Ord, T>(
+    a: PairingRc<P, T>,
+    b: PairingRc<P, T>,
+) -> PairingRc<P, T> {
+    let (root, child) = if b.as_ref().borrow().priority < a.as_ref().borrow().priority { (b, a) } else { (a, b) };
+    let old = root.as_ref().borrow_mut().child.take();
+    if let Some(ref old) = old {
+        old.as_ref().borrow_mut().prev = Some(Rc::downgrade(&child));
+    }
+    {
+        let mut c = child.as_ref().borrow_mut();
+        c.next = old;
+        c.prev = Some(Rc::downgrade(&root));
+    }
+    root.as_ref().borrow_mut().child = Some(child);
+    root
+}
+
+impl<P: Ord + Clone, T> Default for PairingHeap<P, T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<P: Ord + Clone, T> PairingHeap<P, T> {
+    pub fn new() -> Self { Self { root: None, len: 0 } }
+
+    fn _meld_root(&mut self, node: PairingRc<P, T>) {
+        self.root = Some(match self.root.take() {
+            None => node,
+            Some(root) => _pairing_link(root, node),
+        });
+    }
+}
+
+impl<P: Ord + Clone, T> MeldableHeap<P, T> for PairingHeap<P, T> {
+    type Handle = PairingHandle<P, T>;
+
+    fn len(&self) -> usize { self.len }
+
+    fn push(&mut self, priority: P, item: T) -> PairingHandle<P, T> {
+        let node = Rc::new(RefCell::new(PairingNode { priority, item, child: None, next: None, prev: None }));
+        let handle = PairingHandle(Rc::downgrade(&node));
+        self._meld_root(node);
+        self.len += 1;
+        handle
+    }
+
+    fn min_priority(&self) -> Option<P> {
+        self.root.as_ref().map(|r| r.as_ref().borrow().priority.clone())
+    }
+
+    fn pop_min(&mut self) -> Option<(P, T)> {
+        let root = self.root.take()?;
+        self.len -= 1;
+
+        let mut children = Vec::new();
+        let mut cur = root.as_ref().borrow_mut().child.take();
+        while let Some(c) = cur {
+            {
+                let mut b = c.as_ref().borrow_mut();
+                cur = b.next.take();
+                b.prev = None;
+            }
+            children.push(c);
+        }
+
+        // 两趟合并：先从左到右两两配对，再从右到左依次合并
+        let mut paired = Vec::with_capacity(children.len() / 2 + 1);
+        let mut iter = children.into_iter();
+        while let Some(a) = iter.next() {
+            paired.push(match iter.next() {
+                None => a,
+                Some(b) => _pairing_link(a, b),
+            });
+        }
+        self.root = paired.into_iter().rev().fold(None, |acc, n| match acc {
+            None => Some(n),
+            Some(r) => Some(_pairing_link(r, n)),
+        });
+
+        let node = match Rc::try_unwrap(root) {
+            Ok(cell) => cell.into_inner(),
+            Err(_) => unreachable!("popped node is still referenced"),
+        };
+        Some((node.priority, node.item))
+    }
+
+    fn decrease_key(&mut self, handle: &PairingHandle<P, T>, priority: P) -> Result<(), String> {
+        let node = match handle.0.upgrade() {
+            None => return Err(String::from("handle not exists")),
+            Some(rc) => rc,
+        };
+        if priority > node.as_ref().borrow().priority {
+            return Err(String::from("new priority is greater than the current one"));
+        }
+        node.as_ref().borrow_mut().priority = priority;
+
+        let is_root = self.root.as_ref().is_some_and(|r| Rc::ptr_eq(r, &node));
+        if is_root {
+            return Ok(());
+        }
+
+        // 把以 node 为根的子树从兄弟链表中摘下，再与根合并
+        let prev = node.as_ref().borrow_mut().prev.take().and_then(|w| w.upgrade()).unwrap();
+        let next = node.as_ref().borrow_mut().next.take();
+        if let Some(ref n) = next {
+            n.as_ref().borrow_mut().prev = Some(Rc::downgrade(&prev));
+        }
+        let is_first = prev.as_ref().borrow().child.as_ref().is_some_and(|c| Rc::ptr_eq(c, &node));
+        if is_first {
+            prev.as_ref().borrow_mut().child = next;
+        } else {
+            prev.as_ref().borrow_mut().next = next;
+        }
+        self._meld_root(node);
+        Ok(())
+    }
+
+    fn meld(&mut self, mut other: Self) {
+        if let Some(r) = other.root.take() {
+            self._meld_root(r);
+        }
+        self.len += other.len;
+    }
+}
+
+// 逐个拆开节点，避免长链表递归析构导致栈溢出
+impl<P: Ord + Clone, T> Drop for PairingHeap<P, T> {
+    fn drop(&mut self) {
+        let mut stack: Vec<PairingRc<P, T>> = self.root.take().into_iter().collect();
+        while let Some(n) = stack.pop() {
+            let mut b = n.as_ref().borrow_mut();
+            stack.extend(b.child.take());
+            stack.extend(b.next.take());
+        }
+    }
+}
+
+// ---------- 斐波那契堆 ----------
+
+type FibRc<P, T> = Rc<RefCell<FibNode<P, T>>>;
+
+struct FibNode<P, T> {
+    priority: P,
+    item: T,
+    degree: usize,
+    marked: bool,
+    parent: Option<Weak<RefCell<FibNode<P, T>>>>,
+    child: Option<FibRc<P, T>>,
+    left: Option<Weak<RefCell<FibNode<P, T>>>>,
+    right: Option<FibRc<P, T>>,
+}
+
+pub struct FibHandle<P, T>(Weak<RefCell<FibNode<P, T>>>);
+
+impl<P, T> Clone for FibHandle<P, T> {
+    fn clone(&self) -> Self { FibHandle(Weak::clone(&self.0)) }
+}
+
+pub struct FibonacciHeap<P: Ord + Clone, T> {
+    // 根链表，保存尾指针以便 O(1) 拼接
+    head: Option<FibRc<P, T>>,
+    tail: Option<Weak<RefCell<FibNode<P, T>>>>,
+    min: Option<FibRc<P, T>>,
+    len: usize,
+}
+
+impl<P: Ord + Clone, T> Default for FibonacciHeap<P, T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<P: Ord + Clone, T> FibonacciHeap<P, T> {
+    pub fn new() -> Self {
+        Self { head: None, tail: None, min: None, len: 0 }
+    }
+
+    fn _less(a: &FibRc<P, T>, b: &FibRc<P, T>) -> bool {
+        a.as_ref().borrow().priority < b.as_ref().borrow().priority
+    }
+
+    fn _update_min(&mut self, node: &FibRc<P, T>) {
+        let smaller = match self.min {
+            None => true,
+            Some(ref m) => Self::_less(node, m),
+        };
+        if smaller {
+            self.min = Some(Rc::clone(node));
+        }
+    }
+
+    // 插到根链表头部
+    fn _add_root(&mut self, node: FibRc<P, T>) {
+        {
+            let mut b = node.as_ref().borrow_mut();
+            b.parent = None;
+            b.left = None;
+            b.marked = false;
+            b.right = self.head.take();
+        }
+        match node.as_ref().borrow().right {
+            Some(ref h) => h.as_ref().borrow_mut().left = Some(Rc::downgrade(&node)),
+            None => self.tail = Some(Rc::downgrade(&node)),
+        }
+        self.head = Some(node);
+    }
+
+    fn _remove_root(&mut self, node: &FibRc<P, T>) {
+        let prev = node.as_ref().borrow_mut().left.take().and_then(|w| w.upgrade());
+        let next = node.as_ref().borrow_mut().right.take();
+        match next {
+            Some(ref n) => n.as_ref().borrow_mut().left = prev.as_ref().map(Rc::downgrade),
+            None => self.tail = prev.as_ref().map(Rc::downgrade),
+        }
+        match prev {
+            Some(ref p) => p.as_ref().borrow_mut().right = next,
+            None => self.head = next,
+        }
+    }
+
+    // 把 child 挂到 parent 的孩子链表头部
+    fn _link(parent: &FibRc<P, T>, child: FibRc<P, T>) {
+        let old = parent.as_ref().borrow_mut().child.take();
+        if let Some(ref o) = old {
+            o.as_ref().borrow_mut().left = Some(Rc::downgrade(&child));
+        }
+        {
+            let mut c = child.as_ref().borrow_mut();
+            c.parent = Some(Rc::downgrade(parent));
+            c.left = None;
+            c.right = old;
+            c.marked = false;
+        }
+        let mut p = parent.as_ref().borrow_mut();
+        p.child = Some(child);
+        p.degree += 1;
+    }
+
+    // 把 node 从父节点的孩子链表中剪下，放入根链表
+    fn _cut(&mut self, node: FibRc<P, T>, parent: &FibRc<P, T>) {
+        let prev = node.as_ref().borrow_mut().left.take().and_then(|w| w.upgrade());
+        let next = node.as_ref().borrow_mut().right.take();
+        if let Some(ref n) = next {
+            n.as_ref().borrow_mut().left = prev.as_ref().map(Rc::downgrade);
+        }
+        match prev {
+            Some(ref p) => p.as_ref().borrow_mut().right = next,
+            None => parent.as_ref().borrow_mut().child = next,
+        }
+        parent.as_ref().borrow_mut().degree -= 1;
+        self._add_root(node);
+    }
+
+    // 级联剪切：被剪掉第二个孩子的节点也要剪下
+    fn _cascading_cut(&mut self, mut node: FibRc<P, T>) {
+        loop {
+            let parent = node.as_ref().borrow().parent.as_ref().and_then(|w| w.upgrade());
+            let parent = match parent {
+                None => break,
+                Some(p) => p,
+            };
+            if !node.as_ref().borrow().marked {
+                node.as_ref().borrow_mut().marked = true;
+                break;
+            }
+            self._cut(node, &parent);
+            node = parent;
+        }
+    }
+
+    // 合并度数相同的根，直到每个度数至多一棵树
+    fn _consolidate(&mut self) {
+        let mut roots = Vec::new();
+        let mut cur = self.head.take();
+        self.tail = None;
+        while let Some(n) = cur {
+            {
+                let mut b = n.as_ref().borrow_mut();
+                cur = b.right.take();
+                b.left = None;
+            }
+            roots.push(n);
+        }
+
+        let mut table: Vec<Option<FibRc<P, T>>> = Vec::new();
+        for mut x in roots {
+            let mut d = x.as_ref().borrow().degree;
+            loop {
+                if d >= table.len() {
+                    table.resize(d + 1, None);
+                }
+                let mut y = match table[d].take() {
+                    None => break,
+                    Some(y) => y,
+                };
+                if Self::_less(&y, &x) {
+                    ::std::mem::swap(&mut x, &mut y);
+                }
+                Self::_link(&x, y);
+                d += 1;
+            }
+            table[d] = Some(x);
+        }
+
+        self.min = None;
+        for n in table.into_iter().flatten() {
+            self._update_min(&n);
+            self._add_root(n);
+        }
+    }
+}
+
+impl<P: Ord + Clone, T> MeldableHeap<P, T> for FibonacciHeap<P, T> {
+    type Handle = FibHandle<P, T>;
+
+    fn len(&self) -> usize { self.len }
+
+    fn push(&mut self, priority: P, item: T) -> FibHandle<P, T> {
+        let node = Rc::new(RefCell::new(FibNode {
+            priority,
+            item,
+            degree: 0,
+            marked: false,
+            parent: None,
+            child: None,
+            left: None,
+            right: None,
+        }));
+        let handle = FibHandle(Rc::downgrade(&node));
+        self._update_min(&node);
+        self._add_root(node);
+        self.len += 1;
+        handle
+    }
+
+    fn min_priority(&self) -> Option<P> {
+        self.min.as_ref().map(|m| m.as_ref().borrow().priority.clone())
+    }
+
+    fn pop_min(&mut self) -> Option<(P, T)> {
+        let z = self.min.take()?;
+        self._remove_root(&z);
+        let mut child = z.as_ref().borrow_mut().child.take();
+        while let Some(c) = child {
+            child = c.as_ref().borrow_mut().right.take();
+            self._add_root(c);
+        }
+        self.len -= 1;
+        self._consolidate();
+
+        let node = match Rc::try_unwrap(z) {
+            Ok(cell) => cell.into_inner(),
+            Err(_) => unreachable!("popped node is still referenced"),
+        };
+        Some((node.priority, node.item))
+    }
+
+    fn decrease_key(&mut self, handle: &FibHandle<P, T>, priority: P) -> Result<(), String> {
+        let node = match handle.0.upgrade() {
+            None => return Err(String::from("handle not exists")),
+            Some(rc) => rc,
+        };
+        if priority > node.as_ref().borrow().priority {
+            return Err(String::from("new priority is greater than the current one"));
+        }
+        node.as_ref().borrow_mut().priority = priority;
+
+        let parent = node.as_ref().borrow().parent.as_ref().and_then(|w| w.upgrade());
+        if let Some(parent) = parent {
+            if Self::_less(&node, &parent) {
+                self._cut(Rc::clone(&node), &parent);
+                self._cascading_cut(parent);
+            }
+        }
+        self._update_min(&node);
+        Ok(())
+    }
+
+    fn meld(&mut self, mut other: Self) {
+        let other_head = match other.head.take() {
+            None => return,
+            Some(h) => h,
+        };
+        let other_tail = other.tail.take();
+        match self.tail.as_ref().and_then(|w| w.upgrade()) {
+            None => self.head = Some(Rc::clone(&other_head)),
+            Some(t) => {
+                other_head.as_ref().borrow_mut().left = Some(Rc::downgrade(&t));
+                t.as_ref().borrow_mut().right = Some(Rc::clone(&other_head));
+            }
+        }
+        self.tail = other_tail;
+        if let Some(m) = other.min.take() {
+            self._update_min(&m);
+        }
+        self.len += other.len;
+    }
+}
+
+impl<P: Ord + Clone, T> Drop for FibonacciHeap<P, T> {
+    fn drop(&mut self) {
+        self.min = None;
+        let mut stack: Vec<FibRc<P, T>> = self.head.take().into_iter().collect();
+        while let Some(n) = stack.pop() {
+            let mut b = n.as_ref().borrow_mut();
+            stack.extend(b.child.take());
+            stack.extend(b.right.take());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::Lcg;
+
+    fn drain<H: MeldableHeap<u32, usize>>(heap: &mut H) -> Vec<u32> {
+        let mut out = Vec::new();
+        while let Some((p, _)) = heap.pop_min() {
+            out.push(p);
+        }
+        out
+    }
+
+    fn sort_and_meld<H: MeldableHeap<u32, usize> + Default>() {
+        let mut rng = Lcg::new(1);
+        let mut a = H::default();
+        let mut b = H::default();
+        let mut in_a = Vec::new();
+        let mut in_b = Vec::new();
+        for i in 0..500 {
+            let p = rng.below(1000);
+            if i % 3 == 0 {
+                b.push(p, i);
+                in_b.push(p);
+            } else {
+                a.push(p, i);
+                in_a.push(p);
+            }
+        }
+        // 先各弹出几个元素，让两个堆里都有非平凡的树结构
+        in_a.sort();
+        in_b.sort();
+        for _ in 0..10 {
+            assert_eq!(Some(in_a.remove(0)), a.pop_min().map(|(p, _)| p));
+            assert_eq!(Some(in_b.remove(0)), b.pop_min().map(|(p, _)| p));
+        }
+
+        a.meld(b);
+        a.meld(H::default());
+        in_a.extend(in_b);
+        in_a.sort();
+        assert_eq!(in_a.len(), a.len());
+        assert_eq!(in_a.first().cloned(), a.min_priority());
+        assert_eq!(in_a, drain(&mut a));
+        assert!(a.is_empty());
+
+        let mut empty = H::default();
+        empty.meld(a);
+        empty.push(3, 0);
+        assert_eq!(Some((3, 0)), empty.pop_min());
+        assert_eq!(None, empty.pop_min());
+    }
+
+    fn decrease_keys<H: MeldableHeap<u32, usize> + Default>() {
+        let mut rng = Lcg::new(7);
+        let mut heap = H::default();
+        let mut handles = Vec::new();
+        let mut prios = Vec::new();
+        for i in 0..400 {
+            let p = 1000 + rng.below(1000);
+            handles.push(heap.push(p, i));
+            prios.push(p);
+        }
+        // 弹出一次触发合并，之后 decrease_key 会发生剪切
+        let (p, i) = heap.pop_min().unwrap();
+        assert_eq!(prios[i], p);
+        prios[i] = u32::MAX;
+
+        for _ in 0..300 {
+            let i = rng.below(handles.len() as u32) as usize;
+            if prios[i] == u32::MAX {
+                assert!(heap.decrease_key(&handles[i], 0).is_err());
+                continue;
+            }
+            let p = prios[i] - rng.below((prios[i] + 1).min(500));
+            heap.decrease_key(&handles[i], p).unwrap();
+            prios[i] = p;
+            assert!(heap.decrease_key(&handles[i], p + 1).is_err());
+            if rng.below(4) == 0 {
+                let (p, i) = heap.pop_min().unwrap();
+                assert_eq!(prios[i], p);
+                assert_eq!(Some(p), prios.iter().cloned().min());
+                prios[i] = u32::MAX;
+            }
+        }
+
+        let mut expect: Vec<u32> = prios.into_iter().filter(|&p| p != u32::MAX).collect();
+        expect.sort();
+        assert_eq!(expect.len(), heap.len());
+        assert_eq!(expect, drain(&mut heap));
+    }
+
+    #[test]
+    fn pairing_heap() {
+        sort_and_meld::<PairingHeap<u32, usize>>();
+        decrease_keys::<PairingHeap<u32, usize>>();
+    }
+
+    #[test]
+    fn fibonacci_heap() {
+        sort_and_meld::<FibonacciHeap<u32, usize>>();
+        decrease_keys::<FibonacciHeap<u32, usize>>();
+    }
+}
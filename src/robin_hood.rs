@@ -0,0 +1,266 @@
+// Robin Hood 开放寻址哈希表：插入时探测距离短的元素让位给距离长的，删除时把后续元素整体前移（backward shift）
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use tree::search::Lookup;
+
+const MIN_CAPACITY: usize = 8;
+
+struct Bucket<V> {
+    hash: u64,
+    key: String,
+    value: V,
+}
+
+pub struct RobinHoodMap<V> {
+    buckets: Vec<Option<Bucket<V>>>,
+    len: usize,
+}
+
+fn _hash(key: &str) -> u64 {
+    let mut h = DefaultHasher::new();
+    key.hash(&mut h);
+    h.finish()
+}
+
+impl<V> Default for RobinHoodMap<V> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<V> RobinHoodMap<V> {
+    pub fn new() -> Self { Self::with_capacity(MIN_CAPACITY) }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let cap = capacity.max(MIN_CAPACITY).next_power_of_two();
+        Self { buckets: (0..cap).map(|_| None).collect(), len: 0 }
+    }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    pub fn capacity(&self) -> usize { self.buckets.len() }
+
+    fn _mask(&self) -> usize { self.buckets.len() - 1 }
+
+    // 槽位 pos 上的元素距离其理想位置的探测距离
+    fn _dist(&self, hash: u64, pos: usize) -> usize {
+        pos.wrapping_sub(hash as usize) & self._mask()
+    }
+
+    fn _find_pos(&self, key: &str) -> Option<usize> {
+        let hash = _hash(key);
+        let mut pos = hash as usize & self._mask();
+        let mut dist = 0;
+        loop {
+            match self.buckets[pos] {
+                None => return None,
+                Some(ref b) => {
+                    // 遇到比当前探测距离更"富"的元素，说明 key 不可能在更后面
+                    if self._dist(b.hash, pos) < dist {
+                        return None;
+                    }
+                    if b.hash == hash && b.key == key {
+                        return Some(pos);
+                    }
+                }
+            }
+            pos = (pos + 1) & self._mask();
+            dist += 1;
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self._find_pos(key).map(|pos| &self.buckets[pos].as_ref().unwrap().value)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        let pos = self._find_pos(key)?;
+        Some(&mut self.buckets[pos].as_mut().unwrap().value)
+    }
+
+    // 插入，key 已存在时替换并返回旧值
+    pub fn insert(&mut self, key: String, value: V) -> Option<V> {
+        if let Some(pos) = self._find_pos(&key) {
+            let b = self.buckets[pos].as_mut().unwrap();
+            return Some(::std::mem::replace(&mut b.value, value));
+        }
+        // 装载因子超过 7/8 时扩容一倍
+        if (self.len + 1) * 8 > self.buckets.len() * 7 {
+            let cap = self.buckets.len() * 2;
+            self._resize(cap);
+        }
+        let hash = _hash(&key);
+        self._place(Bucket { hash, key, value });
+        self.len += 1;
+        None
+    }
+
+    // 放入一个确定不存在的键
+    fn _place(&mut self, mut bucket: Bucket<V>) {
+        let mask = self._mask();
+        let mut pos = bucket.hash as usize & mask;
+        let mut dist = 0;
+        loop {
+            let resident = match self.buckets[pos] {
+                None => {
+                    self.buckets[pos] = Some(bucket);
+                    return;
+                }
+                Some(ref b) => self._dist(b.hash, pos),
+            };
+            // 劫富济贫：交换后继续为被挤出的元素找位置
+            if resident < dist {
+                bucket = self.buckets[pos].replace(bucket).unwrap();
+                dist = resident;
+            }
+            pos = (pos + 1) & mask;
+            dist += 1;
+        }
+    }
+
+    fn _resize(&mut self, capacity: usize) {
+        let old = ::std::mem::replace(&mut self.buckets, (0..capacity).map(|_| None).collect());
+        for b in old.into_iter().flatten() {
+            self._place(b);
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let mut pos = self._find_pos(key)?;
+        let removed = self.buckets[pos].take().unwrap();
+        self.len -= 1;
+
+        // 后续元素依次前移一格，直到遇到空槽或已在理想位置的元素
+        let mask = self._mask();
+        loop {
+            let next = (pos + 1) & mask;
+            let shift = match self.buckets[next] {
+                None => false,
+                Some(ref b) => self._dist(b.hash, next) > 0,
+            };
+            if !shift {
+                break;
+            }
+            self.buckets[pos] = self.buckets[next].take();
+            pos = next;
+        }
+
+        // 装载因子低于 1/8 时缩容一半
+        if self.len * 8 < self.buckets.len() && self.buckets.len() > MIN_CAPACITY {
+            let cap = self.buckets.len() / 2;
+            self._resize(cap);
+        }
+        Some(removed.value)
+    }
+
+    // 所有元素中最长的探测距离
+    pub fn max_probe_len(&self) -> usize {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter_map(|(pos, b)| b.as_ref().map(|b| self._dist(b.hash, pos)))
+            .max()
+            .unwrap_or(0)
+    }
+
+    // 按槽位顺序遍历，顺序与键的大小无关
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter { inner: self.buckets.iter() }
+    }
+}
+
+impl<V: Clone> Lookup<V> for RobinHoodMap<V> {
+    fn add(&mut self, key: String, value: V) -> Option<V> { self.insert(key, value) }
+
+    fn remove(&mut self, key: &String) -> Option<V> { RobinHoodMap::remove(self, key) }
+
+    fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &String, f: F) -> Option<R> { self.get(key).map(f) }
+
+    fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, key: &String, f: F) -> Option<R> { self.get_mut(key).map(f) }
+}
+
+pub struct Iter<'a, V: 'a> {
+    inner: ::std::slice::Iter<'a, Option<Bucket<V>>>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (&'a String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(b) = self.inner.next()? {
+                return Some((&b.key, &b.value));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use testing::{run_lookup, Lcg};
+
+    // Robin Hood 不变式：每个元素的前一个槽位非空，且其探测距离不小于自己的减一
+    fn check<V>(map: &RobinHoodMap<V>) {
+        let mask = map._mask();
+        let mut count = 0;
+        for (pos, b) in map.buckets.iter().enumerate() {
+            if let Some(ref b) = *b {
+                count += 1;
+                let dist = map._dist(b.hash, pos);
+                if dist > 0 {
+                    let prev = map.buckets[pos.wrapping_sub(1) & mask].as_ref().unwrap();
+                    assert!(map._dist(prev.hash, pos.wrapping_sub(1) & mask) + 1 >= dist);
+                }
+            }
+        }
+        assert_eq!(map.len(), count);
+    }
+
+    #[test]
+    fn same_api_as_lookup() {
+        let mut map = RobinHoodMap::new();
+        run_lookup(&mut map);
+        check(&map);
+
+        let mut map = RobinHoodMap::new();
+        assert!(map.insert(String::from("a"), 1).is_none());
+        assert_eq!(Some(1), map.insert(String::from("a"), 2));
+        *map.get_mut("a").unwrap() += 1;
+        assert_eq!(Some(&3), map.get("a"));
+        assert_eq!(1, map.len());
+    }
+
+    #[test]
+    fn random_against_hashmap() {
+        let mut map = RobinHoodMap::new();
+        let mut model = HashMap::new();
+        let mut rng = Lcg::new(23);
+        for round in 0..6000 {
+            let seed = rng.next_u32();
+            let key = format!("{}", (seed >> 16) % 1500);
+            // 前半段以插入为主，后半段以删除为主，覆盖扩容和缩容
+            let r = (seed >> 8) & 3;
+            let insert = if round < 3000 { r != 0 } else { r == 0 };
+            if insert {
+                assert_eq!(model.insert(key.clone(), seed), map.insert(key, seed));
+            } else {
+                assert_eq!(model.remove(&key), map.remove(&key));
+            }
+            if round % 500 == 0 {
+                check(&map);
+            }
+        }
+        check(&map);
+        assert_eq!(model.len(), map.len());
+        assert!(map.len() * 8 >= map.capacity() || map.capacity() == MIN_CAPACITY);
+
+        let mut actual: Vec<(String, u32)> = map.iter().map(|(k, v)| (k.clone(), *v)).collect();
+        let mut expect: Vec<(String, u32)> = model.into_iter().collect();
+        actual.sort();
+        expect.sort();
+        assert_eq!(expect, actual);
+    }
+}
@@ -0,0 +1,392 @@
+// 跳表：节点存放在 Vec 中，用下标表示各层的后继
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+
+use tree::search::Lookup;
+
+const MAX_LEVEL: usize = 32;
+const DEFAULT_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+// 层数生成器：xorshift64*，每升一层的概率为 1/2，种子相同则结构相同
+#[derive(Clone, Debug)]
+pub struct LevelGenerator {
+    state: u64,
+}
+
+impl LevelGenerator {
+    pub fn new(seed: u64) -> Self {
+        // 全零状态下 xorshift 不会前进
+        Self { state: if seed == 0 { DEFAULT_SEED } else { seed } }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    // 返回 [1, max_level] 之间的层数
+    pub fn random_level(&mut self, max_level: usize) -> usize {
+        let bits = self.next_u64();
+        (bits.trailing_ones() as usize + 1).min(max_level)
+    }
+}
+
+struct SkipNode<V> {
+    key: String,
+    value: V,
+    forward: Vec<Option<usize>>,
+}
+
+pub struct SkipList<V> {
+    nodes: Vec<Option<SkipNode<V>>>,
+    free: Vec<usize>,
+    head: Vec<Option<usize>>,
+    level: usize,
+    len: usize,
+    gen: LevelGenerator,
+}
+
+impl<V> Default for SkipList<V> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<V> SkipList<V> {
+    pub fn new() -> Self { Self::with_seed(DEFAULT_SEED) }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            head: vec![None; MAX_LEVEL],
+            level: 0,
+            len: 0,
+            gen: LevelGenerator::new(seed),
+        }
+    }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    // 当前使用中的最高层数
+    pub fn level(&self) -> usize { self.level }
+
+    pub fn find(&self, key: &str) -> Option<&V> {
+        let idx = self._find(key)?;
+        Some(&self.node(idx).value)
+    }
+
+    pub fn find_mut(&mut self, key: &str) -> Option<&mut V> {
+        let idx = self._find(key)?;
+        Some(&mut self.node_mut(idx).value)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool { self._find(key).is_some() }
+
+    // 插入，key 已存在时替换并返回旧值
+    pub fn add(&mut self, key: String, value: V) -> Option<V> {
+        let mut update = self._find_prev(&key);
+        if let Some(idx) = self.next_of(update[0], 0) {
+            if self.node(idx).key == key {
+                return Some(mem::replace(&mut self.node_mut(idx).value, value));
+            }
+        }
+
+        let lvl = self.gen.random_level(MAX_LEVEL);
+        if lvl > self.level {
+            for u in update[self.level..lvl].iter_mut() {
+                *u = None;
+            }
+            self.level = lvl;
+        }
+
+        let forward = (0..lvl).map(|l| self.next_of(update[l], l)).collect();
+        let idx = self.alloc(SkipNode { key, value, forward });
+        for (l, prev) in update.iter().enumerate().take(lvl) {
+            self.set_next(*prev, l, Some(idx));
+        }
+        self.len += 1;
+        None
+    }
+
+    pub fn update(&mut self, key: &str, value: V) -> Result<(), String> {
+        match self.find_mut(key) {
+            None => Err(format!("node={} not exists", key)),
+            Some(v) => {
+                *v = value;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let update = self._find_prev(key);
+        let idx = self.next_of(update[0], 0)?;
+        if self.node(idx).key != key {
+            return None;
+        }
+
+        let node = self.nodes[idx].take().unwrap();
+        self.free.push(idx);
+        for (l, next) in node.forward.iter().enumerate() {
+            self.set_next(update[l], l, *next);
+        }
+        while self.level > 0 && self.head[self.level - 1].is_none() {
+            self.level -= 1;
+        }
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    pub fn min_val(&self) -> Option<&V> {
+        self.head[0].map(|idx| &self.node(idx).value)
+    }
+
+    pub fn max_val(&self) -> Option<&V> {
+        let mut cur = None;
+        for l in (0..self.level).rev() {
+            while let Some(next) = self.next_of(cur, l) {
+                cur = Some(next);
+            }
+        }
+        cur.map(|idx| &self.node(idx).value)
+    }
+
+    // 按键升序遍历区间内的元素
+    pub fn range<R: RangeBounds<String>>(&self, range: R) -> Range<'_, V> {
+        let cur = match range.start_bound() {
+            Bound::Unbounded => self.head[0],
+            Bound::Included(k) => self.next_of(self._find_prev(k)[0], 0),
+            Bound::Excluded(k) => {
+                let cur = self.next_of(self._find_prev(k)[0], 0);
+                match cur {
+                    Some(idx) if self.node(idx).key == *k => self.node(idx).forward[0],
+                    _ => cur,
+                }
+            }
+        };
+        Range {
+            list: self,
+            cur,
+            end: range.end_bound().cloned(),
+        }
+    }
+
+    pub fn iter(&self) -> Range<'_, V> { self.range(..) }
+
+    fn node(&self, idx: usize) -> &SkipNode<V> {
+        self.nodes[idx].as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, idx: usize) -> &mut SkipNode<V> {
+        self.nodes[idx].as_mut().unwrap()
+    }
+
+    // prev 为 None 表示头节点
+    fn next_of(&self, prev: Option<usize>, level: usize) -> Option<usize> {
+        match prev {
+            None => self.head[level],
+            Some(idx) => self.node(idx).forward[level],
+        }
+    }
+
+    fn set_next(&mut self, prev: Option<usize>, level: usize, next: Option<usize>) {
+        match prev {
+            None => self.head[level] = next,
+            Some(idx) => self.node_mut(idx).forward[level] = next,
+        }
+    }
+
+    fn alloc(&mut self, node: SkipNode<V>) -> usize {
+        match self.free.pop() {
+            Some(idx) => {
+                self.nodes[idx] = Some(node);
+                idx
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    // 每一层上最后一个小于 key 的节点
+    fn _find_prev(&self, key: &str) -> Vec<Option<usize>> {
+        let mut update = vec![None; MAX_LEVEL];
+        let mut cur = None;
+        for l in (0..self.level).rev() {
+            while let Some(next) = self.next_of(cur, l) {
+                if self.node(next).key.as_str() < key {
+                    cur = Some(next);
+                } else {
+                    break;
+                }
+            }
+            update[l] = cur;
+        }
+        update
+    }
+
+    fn _find(&self, key: &str) -> Option<usize> {
+        let mut cur = None;
+        for l in (0..self.level).rev() {
+            while let Some(next) = self.next_of(cur, l) {
+                if self.node(next).key.as_str() < key {
+                    cur = Some(next);
+                } else {
+                    break;
+                }
+            }
+        }
+        match self.next_of(cur, 0) {
+            Some(idx) if self.node(idx).key == key => Some(idx),
+            _ => None,
+        }
+    }
+}
+
+impl<V: Clone> SkipList<V> {
+    pub fn find_and_clone(&self, key: &str) -> Option<V> { self.find(key).cloned() }
+
+    pub fn min_val_clone(&self) -> Option<V> { self.min_val().cloned() }
+
+    pub fn max_val_clone(&self) -> Option<V> { self.max_val().cloned() }
+}
+
+#[allow(clippy::ptr_arg)]
+impl<V: Clone> Lookup<V> for SkipList<V> {
+    fn add(&mut self, key: String, value: V) -> Option<V> { SkipList::add(self, key, value) }
+
+    fn remove(&mut self, key: &String) -> Option<V> { SkipList::remove(self, key) }
+
+    fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &String, f: F) -> Option<R> { self.find(key).map(f) }
+
+    fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, key: &String, f: F) -> Option<R> {
+        self.find_mut(key).map(f)
+    }
+}
+
+pub struct Range<'a, V: 'a> {
+    list: &'a SkipList<V>,
+    cur: Option<usize>,
+    end: Bound<String>,
+}
+
+impl<'a, V> Iterator for Range<'a, V> {
+    type Item = (&'a String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.list.node(self.cur?);
+        let past_end = match self.end {
+            Bound::Included(ref e) => node.key > *e,
+            Bound::Excluded(ref e) => node.key >= *e,
+            Bound::Unbounded => false,
+        };
+        if past_end {
+            self.cur = None;
+            return None;
+        }
+        self.cur = node.forward[0];
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    // 每一层都必须有序，且上层是下层的子序列
+    fn check<V>(list: &SkipList<V>) {
+        let mut lower: Vec<usize> = Vec::new();
+        for l in 0..list.level() {
+            let mut layer = Vec::new();
+            let mut cur = list.head[l];
+            while let Some(idx) = cur {
+                layer.push(idx);
+                cur = list.node(idx).forward[l];
+            }
+            assert!(!layer.is_empty());
+            assert!(layer.windows(2).all(|w| list.node(w[0]).key < list.node(w[1]).key));
+            if l == 0 {
+                assert_eq!(list.len(), layer.len());
+            } else {
+                assert!(layer.iter().all(|idx| lower.contains(idx)));
+            }
+            lower = layer;
+        }
+        assert!(list.head[list.level()..].iter().all(|h| h.is_none()));
+    }
+
+    #[test]
+    fn add_find_remove() {
+        let mut list = SkipList::new();
+        assert_eq!(None, list.min_val());
+        for i in (0..100).rev() {
+            assert_eq!(None, list.add(format!("{:03}", i), i));
+        }
+        check(&list);
+        assert_eq!(100, list.len());
+        assert_eq!(Some(&7), list.find("007"));
+        assert_eq!(Some(7), list.add(String::from("007"), -7));
+        assert_eq!(Some(-7), list.find_and_clone("007"));
+        assert!(list.update("100", 1).is_err());
+        assert_eq!(Some(0), list.min_val_clone());
+        assert_eq!(Some(99), list.max_val_clone());
+
+        for i in 0..100 {
+            assert!(list.remove(&format!("{:03}", i)).is_some());
+        }
+        check(&list);
+        assert!(list.is_empty());
+        assert_eq!(0, list.level());
+        assert_eq!(None, list.remove("000"));
+    }
+
+    #[test]
+    fn range_scan() {
+        let mut list = SkipList::with_seed(3);
+        for i in 0..50 {
+            list.add(format!("{:02}", i * 2), i * 2);
+        }
+
+        let vals: Vec<i32> = list.range(String::from("10")..String::from("20")).map(|(_, v)| *v).collect();
+        assert_eq!(vec![10, 12, 14, 16, 18], vals);
+        let vals: Vec<i32> = list.range((Bound::Excluded(String::from("10")), Bound::Included(String::from("16")))).map(|(_, v)| *v).collect();
+        assert_eq!(vec![12, 14, 16], vals);
+        assert_eq!(50, list.iter().count());
+    }
+
+    #[test]
+    fn same_seed_same_shape() {
+        let mut a = SkipList::with_seed(42);
+        let mut b = SkipList::with_seed(42);
+        let mut model = BTreeMap::new();
+        let mut gen = LevelGenerator::new(7);
+        let mut removed = 0;
+        for _ in 0..3000 {
+            let r = gen.next_u64();
+            let key = format!("{}", r % 300);
+            // 操作类型取自与 key 无关的高位，删除才会命中已有的元素
+            if (r >> 32) & 3 == 0 {
+                let expect = model.remove(&key);
+                removed += expect.is_some() as usize;
+                assert_eq!(expect, a.remove(&key));
+                b.remove(&key);
+            } else {
+                assert_eq!(model.insert(key.clone(), r), a.add(key.clone(), r));
+                b.add(key, r);
+            }
+        }
+        check(&a);
+        assert_eq!(a.level(), b.level());
+        assert_eq!(a.head, b.head);
+
+        assert!(removed > 100);
+        let expect: Vec<(&String, &u64)> = model.iter().collect();
+        let actual: Vec<(&String, &u64)> = a.iter().collect();
+        assert_eq!(expect, actual);
+    }
+}
@@ -0,0 +1,96 @@
+// 测试共用的工具
+use std::collections::BTreeMap;
+use tree::search::Lookup;
+
+// 确定性的线性同余随机数，所有随机测试共用同一个生成器
+pub struct Lcg(u32);
+
+impl Lcg {
+    pub fn new(seed: u32) -> Self { Lcg(seed) }
+
+    // 推进一步并返回完整状态，低位周期短，取数时用高位
+    pub fn next_u32(&mut self) -> u32 {
+        self.0 = self.0.wrapping_mul(1103515245).wrapping_add(12345);
+        self.0
+    }
+
+    // [0, n) 内的数，取高 16 位
+    pub fn below(&mut self, n: u32) -> u32 {
+        (self.next_u32() >> 16) % n
+    }
+}
+
+// 随机插入、删除、查找 rounds 次，每一步的返回值都和 BTreeMap 对照，返回模型供调用方继续核对
+pub fn check_against_model<L: Lookup<u32>>(store: &mut L, seed: u32, rounds: usize, keys: u32) -> BTreeMap<String, u32> {
+    let mut rng = Lcg::new(seed);
+    let mut model = BTreeMap::new();
+    for _ in 0..rounds {
+        let key = format!("{}", rng.below(keys));
+        let v = rng.next_u32();
+        match (v >> 8) & 3 {
+            0 => assert_eq!(model.remove(&key), store.remove(&key)),
+            1 => assert_eq!(model.get(&key).cloned(), store.find_and_clone(&key)),
+            _ => assert_eq!(model.insert(key.clone(), v), store.add(key, v)),
+        }
+    }
+    for (k, v) in model.iter() {
+        assert_eq!(Some(*v), store.find_and_clone(k));
+    }
+    model
+}
+
+// 只依赖 Lookup 的通用用例，所有实现跑同一份，包括重复键的约定
+pub fn run_lookup<L: Lookup<u32>>(store: &mut L) {
+    for i in 0..200u32 {
+        assert_eq!(None, store.add(format!("key{}", i * 7 % 200), i));
+    }
+    assert_eq!(Some(0), store.find_and_clone(&String::from("key0")));
+    assert_eq!(Some(1), store.find_and_clone(&String::from("key7")));
+    assert!(!store.contains_key(&String::from("key200")));
+
+    // 重复的 key 替换旧值，删除一次之后就不存在了
+    assert_eq!(Some(2), store.add(String::from("key14"), 140));
+    assert_eq!(Some(140), store.remove(&String::from("key14")));
+    assert!(!store.contains_key(&String::from("key14")));
+    assert_eq!(None, store.add(String::from("key14"), 2));
+
+    assert!(store.update(&String::from("key7"), 70).is_ok());
+    assert!(store.update(&String::from("nope"), 1).is_err());
+    assert_eq!(Some(70), store.find_and_clone(&String::from("key7")));
+    assert_eq!(Some(71), store.with_value_mut(&String::from("key7"), |v| {
+        *v += 1;
+        *v
+    }));
+    assert_eq!(Some(true), store.with_value(&String::from("key7"), |v| *v == 71));
+
+    for i in (0..200).filter(|i| i % 3 == 0) {
+        assert!(store.remove(&format!("key{}", i)).is_some());
+    }
+    assert_eq!(None, store.remove(&String::from("key0")));
+    for i in 0..200 {
+        assert_eq!(i % 3 != 0, store.contains_key(&format!("key{}", i)));
+    }
+}
+
+#[test]
+fn lookup_impls_agree() {
+    use btree::BPlusTree;
+    use robin_hood::RobinHoodMap;
+    use skiplist::SkipList;
+    use tree::arena::ArenaAvl;
+    use tree::avl::AVLTree;
+    use tree::boxed::BoxAvlTree;
+    use tree::bst::PlainBst;
+    use tree::sync_avl::SyncAvlTree;
+
+    run_lookup(&mut RobinHoodMap::new());
+    run_lookup(&mut AVLTree::<u32>::new());
+    run_lookup(&mut PlainBst::<u32>::new());
+    run_lookup(&mut BoxAvlTree::new());
+    run_lookup(&mut ArenaAvl::new());
+    run_lookup(&mut BPlusTree::with_order(4));
+    run_lookup(&mut SkipList::new());
+    run_lookup(&mut SyncAvlTree::<u32>::new());
+    #[cfg(feature = "raw-avl")]
+    run_lookup(&mut ::tree::raw::RawAvlTree::<u32>::new());
+}
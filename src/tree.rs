@@ -1,20 +1,48 @@
 // 抽象数据结构
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Ref, RefCell, RefMut};
 use std::rc::Weak;
 use std::fmt::Display;
+use std::mem;
+use std::ops::{Bound, Deref, DerefMut, RangeBounds};
+use aggregate::{Monoid, Sum, Min, Max, Count};
 
 pub mod binary {
     use super::*;
 
+    // 子树摘要：每个节点缓存 combine(左子树, measure(value), 右子树)
+    pub trait Summary<V>: Monoid {
+        fn measure(value: &V) -> Self::Item;
+    }
+
+    impl<V> Summary<V> for () {
+        fn measure(_: &V) {}
+    }
+
+    impl<V> Summary<V> for Count {
+        fn measure(_: &V) -> usize { 1 }
+    }
+
+    impl<V: Clone + Into<i64>> Summary<V> for Sum {
+        fn measure(value: &V) -> i64 { value.clone().into() }
+    }
+
+    impl<V: Clone + Into<i64>> Summary<V> for Min {
+        fn measure(value: &V) -> i64 { value.clone().into() }
+    }
+
+    impl<V: Clone + Into<i64>> Summary<V> for Max {
+        fn measure(value: &V) -> i64 { value.clone().into() }
+    }
+
     // 遍历状态
     enum NodeCheckStatus {
         Done,
-        UNDO,
+        Undo,
     }
 
     #[derive(Getters, MutGetters, Setters, Clone, Debug)]
-    pub struct BinaryNode<V: Clone + Display>
+    pub struct BinaryNode<V: Clone + Display, S: Summary<V> = ()>
     {
         #[get = "pub"]
         #[set = "pub"]
@@ -24,38 +52,49 @@ pub mod binary {
         #[get = "pub"]
         #[set = "pub"]
         #[get_mut = "pub"]
-        value: Rc<RefCell<V>>,
+        value: V,
 
         #[get = "pub"]
         #[set = "pub"]
         #[get_mut = "pub"]
-        top: Option<Weak<RefCell<BinaryNode<V>>>>,
+        top: Option<Weak<RefCell<BinaryNode<V, S>>>>,
 
         #[get = "pub"]
         #[set = "pub"]
         #[get_mut = "pub"]
-        left: Option<Rc<RefCell<BinaryNode<V>>>>,
+        left: Option<Rc<RefCell<BinaryNode<V, S>>>>,
 
         #[get = "pub"]
         #[set = "pub"]
         #[get_mut = "pub"]
-        right: Option<Rc<RefCell<BinaryNode<V>>>>,
+        right: Option<Rc<RefCell<BinaryNode<V, S>>>>,
+
+        // 由 refresh 维护，外部只读
+        #[get = "pub"]
+        summary: S::Item,
     }
 
     impl<V: Clone + Display> BinaryNode<V> {
-        pub fn new(key: String, value: V) -> Self {
+        pub fn new(key: String, value: V) -> Self { Self::with_summary(key, value) }
+    }
+
+    impl<V: Clone + Display, S: Summary<V>> BinaryNode<V, S> {
+        pub fn with_summary(key: String, value: V) -> Self {
             Self {
                 key,
-                value: Rc::new(RefCell::new(value)),
+                summary: S::measure(&value),
+                value,
                 top: None,
                 left: None,
                 right: None,
             }
         }
 
+        pub fn into_value(self) -> V { self.value }
+
         pub fn depth(&self) -> i32 {
             let mut d = 0;
-            let mut cur: Rc<RefCell<BinaryNode<V>>>;
+            let mut cur: Rc<RefCell<BinaryNode<V, S>>>;
 
             match self.top {
                 None => return d,
@@ -90,20 +129,18 @@ pub mod binary {
         }
 
         pub fn height(&self) -> i32 {
-            type N<V> = Rc<RefCell<BinaryNode<V>>>;
+            type N<V, S> = Rc<RefCell<BinaryNode<V, S>>>;
             let mut h = 0;
             let mut max_h = 0;
-            let mut stack: Vec<(N<V>, NodeCheckStatus)> = Vec::new();
+            let mut stack: Vec<(N<V, S>, NodeCheckStatus)> = Vec::new();
 
-            if self.left.is_some() {
-                let t = self.left.as_ref();
-                stack.push((Rc::clone(t.unwrap()), NodeCheckStatus::UNDO));
+            if let Some(ref t) = self.left {
+                stack.push((Rc::clone(t), NodeCheckStatus::Undo));
             }
-            if self.right.is_some() {
-                let t = self.right.as_ref();
-                stack.push((Rc::clone(t.unwrap()), NodeCheckStatus::UNDO));
+            if let Some(ref t) = self.right {
+                stack.push((Rc::clone(t), NodeCheckStatus::Undo));
             }
-            if stack.len() == 0 {
+            if stack.is_empty() {
                 return h;
             }
 
@@ -118,7 +155,7 @@ pub mod binary {
                                 }
                                 h -= 1;
                             }
-                            NodeCheckStatus::UNDO => {
+                            NodeCheckStatus::Undo => {
                                 let node_borrow = node.as_ref().borrow();
                                 let left = node_borrow.left.as_ref();
                                 let right = node_borrow.right.as_ref();
@@ -129,11 +166,11 @@ pub mod binary {
                                 if left.is_none() && right.is_none() {
                                     continue;
                                 }
-                                if left.is_some() {
-                                    stack.push((Rc::clone(left.unwrap()), NodeCheckStatus::UNDO));
+                                if let Some(left) = left {
+                                    stack.push((Rc::clone(left), NodeCheckStatus::Undo));
                                 }
-                                if right.is_some() {
-                                    stack.push((Rc::clone(right.unwrap()), NodeCheckStatus::UNDO));
+                                if let Some(right) = right {
+                                    stack.push((Rc::clone(right), NodeCheckStatus::Undo));
                                 }
                             }
                         }
@@ -158,10 +195,10 @@ pub mod binary {
     }
 
     // 添加左节点, 返回原左节点
-    pub fn link_left<V: Clone + Display>(
-        parent_node: Rc<RefCell<BinaryNode<V>>>,
-        child_node: Option<Rc<RefCell<BinaryNode<V>>>>,
-    ) -> Option<Rc<RefCell<BinaryNode<V>>>> {
+    pub fn link_left<V: Clone + Display, S: Summary<V>>(
+        parent_node: Rc<RefCell<BinaryNode<V, S>>>,
+        child_node: Option<Rc<RefCell<BinaryNode<V, S>>>>,
+    ) -> Option<Rc<RefCell<BinaryNode<V, S>>>> {
         let r_left = if parent_node.as_ref().borrow_mut().left().is_none() {
             None
         } else {
@@ -169,23 +206,22 @@ pub mod binary {
             t.as_ref().borrow_mut().set_top(None);
             Some(t)
         };
-        if child_node.is_some() {
-            child_node.as_ref()
-                .unwrap()
-                .as_ref()
+        if let Some(ref child) = child_node {
+            child.as_ref()
                 .borrow_mut()
                 .set_top(Some(Rc::downgrade(&parent_node)));
         }
         parent_node.as_ref().borrow_mut().set_left(child_node);
+        refresh(&parent_node);
 
         r_left
     }
 
     // 添加右边节点, 返回原右节点
-    pub fn link_right<V: Clone + Display>(
-        parent_node: Rc<RefCell<BinaryNode<V>>>,
-        child_node: Option<Rc<RefCell<BinaryNode<V>>>>,
-    ) -> Option<Rc<RefCell<BinaryNode<V>>>> {
+    pub fn link_right<V: Clone + Display, S: Summary<V>>(
+        parent_node: Rc<RefCell<BinaryNode<V, S>>>,
+        child_node: Option<Rc<RefCell<BinaryNode<V, S>>>>,
+    ) -> Option<Rc<RefCell<BinaryNode<V, S>>>> {
         let r_right = if parent_node.as_ref().borrow_mut().right().is_none() {
             None
         } else {
@@ -194,32 +230,56 @@ pub mod binary {
             Some(t)
         };
 
-        if child_node.is_some() {
+        if let Some(ref child) = child_node {
             let weak_rc = Rc::downgrade(&parent_node);
-            child_node
-                .as_ref()
-                .unwrap()
-                .as_ref()
+            child.as_ref()
                 .borrow_mut()
                 .set_top(Some(weak_rc));
         }
         parent_node.as_ref().borrow_mut().set_right(child_node);
+        refresh(&parent_node);
 
         r_right
     }
 
-    pub fn is_left_child<V: Clone + Display>(
-        parent_node: Rc<RefCell<BinaryNode<V>>>,
-        child_node: Rc<RefCell<BinaryNode<V>>>,
-    ) -> bool {
-        let left_ptr = match parent_node.as_ref().borrow().left() {
-            None => None,
-            Some(ref rc) => Some(rc.as_ptr())
+    // 根据孩子重新计算节点的摘要
+    pub fn refresh<V: Clone + Display, S: Summary<V>>(node: &Rc<RefCell<BinaryNode<V, S>>>) {
+        let summary = {
+            let borrow = node.as_ref().borrow();
+            let mut r = match borrow.left() {
+                None => S::identity(),
+                Some(ref rc) => rc.as_ref().borrow().summary().clone()
+            };
+            r = S::combine(&r, &S::measure(borrow.value()));
+            if let Some(ref rc) = *borrow.right() {
+                r = S::combine(&r, rc.as_ref().borrow().summary());
+            }
+            r
         };
+        node.as_ref().borrow_mut().summary = summary;
+    }
+
+    // 从 node 开始沿 top 向上刷新到根
+    pub fn refresh_up<V: Clone + Display, S: Summary<V>>(node: Option<Rc<RefCell<BinaryNode<V, S>>>>) {
+        let mut cur = node;
+        while let Some(rc) = cur {
+            refresh(&rc);
+            cur = match rc.as_ref().borrow().top() {
+                None => None,
+                Some(ref weak) => weak.upgrade()
+            };
+        }
+    }
+
+    pub fn is_left_child<V: Clone + Display, S: Summary<V>>(
+        parent_node: Rc<RefCell<BinaryNode<V, S>>>,
+        child_node: Rc<RefCell<BinaryNode<V, S>>>,
+    ) -> bool {
+        let left_ptr = parent_node.as_ref().borrow().left().as_ref().map(|rc| rc.as_ptr());
         left_ptr.is_some() && child_node.as_ptr() == left_ptr.unwrap()
     }
 
-    pub fn take_from_top<V: Clone + Display>(node: &Rc<RefCell<BinaryNode<V>>>) -> Option<Rc<RefCell<BinaryNode<V>>>> {
+    pub fn take_from_top<V: Clone + Display, S: Summary<V>>(node: &Rc<RefCell<BinaryNode<V, S>>>) -> Option<Rc<RefCell<BinaryNode<V, S>>>> {
         if node.as_ref().borrow().top().is_some() {
             let rc = node.as_ref().borrow().top().as_ref().unwrap().upgrade().unwrap();
             if is_left_child(Rc::clone(&rc), Rc::clone(node)) {
@@ -236,14 +296,19 @@ pub mod binary {
 
 pub mod search {
     use super::*;
-    use super::binary::{BinaryNode, is_left_child};
+    use super::binary::{BinaryNode, Summary, is_left_child, link_left, link_right, refresh_up};
+    use super::memory::{binary_usage, HeapSize, MemoryUsage};
+    use super::audit::{audit, AuditReport};
 
-    pub trait SearchTree<V: Clone + Display> {
-        fn root(&self) -> &Option<Rc<RefCell<BinaryNode<V>>>>;
-        fn add_node(&mut self, node_rc: Rc<RefCell<BinaryNode<V>>>);
+    pub trait SearchTree<V: Clone + Display, S: Summary<V> = ()> {
+        fn root(&self) -> &Option<Rc<RefCell<BinaryNode<V, S>>>>;
+        fn add_node(&mut self, node_rc: Rc<RefCell<BinaryNode<V, S>>>);
+        // 摘下 key 对应的节点并返回，返回的节点不再与树相连
+        #[allow(clippy::ptr_arg)]
+        fn remove_node(&mut self, key: &String) -> Option<Rc<RefCell<BinaryNode<V, S>>>>;
 
         // 以下为默认实现
-        fn find_node(&self, key: &String) -> Option<Rc<RefCell<BinaryNode<V>>>> {
+        fn find_node(&self, key: &String) -> Option<Rc<RefCell<BinaryNode<V, S>>>> {
             let r = self.root();
             match *r {
                 None => None,
@@ -251,36 +316,52 @@ pub mod search {
             }
         }
 
-        fn find(&self, key: &String) -> Option<Rc<RefCell<V>>> {
-            match self.find_node(key) {
-                None => None,
-                Some(r) => Some(Rc::clone(r.as_ref().borrow().value()))
-            }
+        // 借出值的只读守卫，守卫存在期间树不能被修改
+        fn get(&self, key: &String) -> Option<ValueRef<'_, V, S>> {
+            self.find_node(key).map(ValueRef::new)
+        }
+
+        // 借出值的可写守卫，守卫释放时沿 top 刷新摘要
+        fn get_mut(&mut self, key: &String) -> Option<ValueRefMut<'_, V, S>> {
+            self.find_node(key).map(ValueRefMut::new)
         }
 
         fn find_and_clone(&self, key: &String) -> Option<V> {
-            match self.find(key) {
-                None => None,
-                Some(rc) => Some(rc.as_ref().borrow().clone())
-            }
+            self.get(key).map(|v| v.clone())
         }
 
         fn add(&mut self, key: String, value: V) {
-            let node_rc = Rc::new(RefCell::new(BinaryNode::new(key, value)));
+            let node_rc = Rc::new(RefCell::new(BinaryNode::with_summary(key, value)));
             self.add_node(node_rc);
         }
 
         fn update(&mut self, key: &String, value: V) -> Result<(), String> {
             let dest = self.find_node(key);
             match dest {
-                None => Err(String::from(format!("node={} not exists", key))),
+                None => Err(format!("node={} not exists", key)),
                 Some(rc) => {
-                    rc.as_ref().borrow_mut().set_value(Rc::new(RefCell::new(value)));
+                    rc.as_ref().borrow_mut().set_value(value);
+                    refresh_up(Some(rc));
                     Ok(())
                 }
             }
         }
 
+        // 在借用期间访问值，不克隆
+        fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &String, f: F) -> Option<R> {
+            let node = self.find_node(key)?;
+            let r = f(node.as_ref().borrow().value());
+            Some(r)
+        }
+
+        // 修改后沿 top 刷新摘要
+        fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, key: &String, f: F) -> Option<R> {
+            let node = self.find_node(key)?;
+            let r = f(node.as_ref().borrow_mut().value_mut());
+            refresh_up(Some(node));
+            Some(r)
+        }
+
         fn height(&self) -> i32 {
             let root = self.root();
             match root {
@@ -290,9 +371,261 @@ pub mod search {
         }
 
         fn depth(&self) -> i32 { self.height() }
+
+        fn remove(&mut self, key: &String) -> Option<V> {
+            self.remove_node(key).map(take_node_value)
+        }
+
+        fn memory_usage(&self) -> MemoryUsage where V: HeapSize {
+            binary_usage(self.root())
+        }
+
+        // 检查整棵树的引用计数与 top 指针
+        fn audit(&self) -> AuditReport { audit(self.root().as_ref()) }
     }
 
-    fn _find_node<V: Clone + Display>(key: &String, mut cur: Rc<RefCell<BinaryNode<V>>>) -> Option<Rc<RefCell<BinaryNode<V>>>> {
+    // 只按 key 存取值的接口，查找树、哈希表、B+树和跳表共用，便于同一套测试和基准对比
+    // 约定键唯一：add 遇到已有的 key 时替换旧值并返回它，不会插入第二个同名元素；
+    // 这与 SearchTree::add（保留重复键）不同，查找树的实现先查找再决定替换还是插入
+    #[allow(clippy::ptr_arg)]
+    pub trait Lookup<V: Clone> {
+        fn add(&mut self, key: String, value: V) -> Option<V>;
+        fn remove(&mut self, key: &String) -> Option<V>;
+        fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &String, f: F) -> Option<R>;
+        fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, key: &String, f: F) -> Option<R>;
+
+        // 以下为默认实现
+        fn update(&mut self, key: &String, value: V) -> Result<(), String> {
+            self.with_value_mut(key, |v| *v = value).ok_or_else(|| format!("node={} not exists", key))
+        }
+
+        fn find_and_clone(&self, key: &String) -> Option<V> { self.with_value(key, V::clone) }
+
+        fn contains_key(&self, key: &String) -> bool { self.with_value(key, |_| ()).is_some() }
+    }
+
+    // 取出已摘下节点中的值：unlink_node 之后树里已没有指向它的强引用，只剩调用方手里这一个
+    pub fn take_node_value<V: Clone + Display, S: Summary<V>>(rc: Rc<RefCell<BinaryNode<V, S>>>) -> V {
+        match Rc::try_unwrap(rc) {
+            Ok(cell) => cell.into_inner().into_value(),
+            Err(_) => panic!("removed node is still shared"),
+        }
+    }
+
+    // get 返回的只读守卫：自己持有节点的 Rc，并借用其中的值
+    pub struct ValueRef<'a, V: Clone + Display + 'a, S: Summary<V> + 'a = ()> {
+        // 字段按声明顺序析构，借用先于 Rc 释放
+        value: Ref<'a, V>,
+        _node: Rc<RefCell<BinaryNode<V, S>>>,
+    }
+
+    impl<'a, V: Clone + Display, S: Summary<V>> ValueRef<'a, V, S> {
+        pub(crate) fn new(node: Rc<RefCell<BinaryNode<V, S>>>) -> Self {
+            // SAFETY: 节点分配在堆上，_node 存活期间不会移动或释放，而借用 value 先于 _node 析构
+            let cell: &'a RefCell<BinaryNode<V, S>> = unsafe { &*Rc::as_ptr(&node) };
+            Self { value: Ref::map(cell.borrow(), |n| n.value()), _node: node }
+        }
+    }
+
+    impl<'a, V: Clone + Display, S: Summary<V>> Deref for ValueRef<'a, V, S> {
+        type Target = V;
+
+        fn deref(&self) -> &V { &self.value }
+    }
+
+    // get_mut 返回的可写守卫，释放时先归还借用再刷新摘要
+    pub struct ValueRefMut<'a, V: Clone + Display + 'a, S: Summary<V> + 'a = ()> {
+        value: Option<RefMut<'a, V>>,
+        node: Rc<RefCell<BinaryNode<V, S>>>,
+    }
+
+    impl<'a, V: Clone + Display, S: Summary<V>> ValueRefMut<'a, V, S> {
+        fn new(node: Rc<RefCell<BinaryNode<V, S>>>) -> Self {
+            // SAFETY: 同 ValueRef::new，Drop 中先释放 value 再使用 node
+            let cell: &'a RefCell<BinaryNode<V, S>> = unsafe { &*Rc::as_ptr(&node) };
+            Self { value: Some(RefMut::map(cell.borrow_mut(), |n| n.value_mut())), node }
+        }
+    }
+
+    impl<'a, V: Clone + Display, S: Summary<V>> Deref for ValueRefMut<'a, V, S> {
+        type Target = V;
+
+        fn deref(&self) -> &V { self.value.as_ref().unwrap() }
+    }
+
+    impl<'a, V: Clone + Display, S: Summary<V>> DerefMut for ValueRefMut<'a, V, S> {
+        fn deref_mut(&mut self) -> &mut V { self.value.as_mut().unwrap() }
+    }
+
+    impl<'a, V: Clone + Display, S: Summary<V>> Drop for ValueRefMut<'a, V, S> {
+        fn drop(&mut self) {
+            self.value = None;
+            refresh_up(Some(Rc::clone(&self.node)));
+        }
+    }
+
+    // 按二叉查找树规则挂上新节点（相等的键放左边），返回新节点的父节点
+    pub fn insert_node<V: Clone + Display, S: Summary<V>>(
+        root: &mut Option<Rc<RefCell<BinaryNode<V, S>>>>,
+        node_rc: Rc<RefCell<BinaryNode<V, S>>>,
+    ) -> Option<Rc<RefCell<BinaryNode<V, S>>>> {
+        let mut cur = match *root {
+            None => {
+                *root = Some(node_rc);
+                return None;
+            }
+            Some(ref r) => Rc::clone(r)
+        };
+
+        let top = loop {
+            let cur_t = Rc::clone(&cur);
+            let is_less = cur_t.as_ref().borrow().key() >= node_rc.as_ref().borrow().key();
+
+            if !is_less {
+                if cur_t.as_ref().borrow().right().is_some() {
+                    cur = Rc::clone(cur_t.as_ref().borrow().right().as_ref().unwrap());
+                } else {
+                    link_right(Rc::clone(&cur), Some(node_rc));
+                    break Some(cur);
+                }
+            } else {
+                if cur_t.as_ref().borrow().left().is_some() {
+                    cur = Rc::clone(cur_t.as_ref().borrow().left().as_ref().unwrap());
+                } else {
+                    link_left(Rc::clone(&cur), Some(node_rc));
+                    break Some(cur);
+                }
+            }
+        };
+        refresh_up(top.clone());
+        top
+    }
+
+    // 不做平衡地摘下节点：有两个孩子时用右子树的最小节点顶替它的位置
+    // 返回结构发生变化的最深节点，平衡树从这里开始向上调整
+    pub fn unlink_node<V: Clone + Display, S: Summary<V>>(
+        root: &mut Option<Rc<RefCell<BinaryNode<V, S>>>>,
+        node: &Rc<RefCell<BinaryNode<V, S>>>,
+    ) -> Option<Rc<RefCell<BinaryNode<V, S>>>> {
+        let top = match node.as_ref().borrow().top() {
+            None => None,
+            Some(ref weak) => weak.upgrade()
+        };
+        let has_left = node.as_ref().borrow().left().is_some();
+        let has_right = node.as_ref().borrow().right().is_some();
+
+        let start = if has_left && has_right {
+            let left = link_left(Rc::clone(node), None);
+            let right = link_right(Rc::clone(node), None).unwrap();
+
+            let mut succ = Rc::clone(&right);
+            loop {
+                let next = match succ.as_ref().borrow().left() {
+                    None => None,
+                    Some(ref rc) => Some(Rc::clone(rc))
+                };
+                match next {
+                    None => break,
+                    Some(rc) => succ = rc
+                }
+            }
+
+            let start = if Rc::ptr_eq(&succ, &right) {
+                Rc::clone(&succ)
+            } else {
+                let succ_top = succ.as_ref().borrow().top().as_ref().unwrap().upgrade().unwrap();
+                let succ_right = link_right(Rc::clone(&succ), None);
+                link_left(Rc::clone(&succ_top), succ_right);
+                link_right(Rc::clone(&succ), Some(right));
+                succ_top
+            };
+            link_left(Rc::clone(&succ), left);
+            _replace_child(root, top, node, Some(succ));
+            Some(start)
+        } else {
+            let child = if has_left {
+                link_left(Rc::clone(node), None)
+            } else {
+                link_right(Rc::clone(node), None)
+            };
+            _replace_child(root, top.clone(), node, child);
+            top
+        };
+        refresh_up(start.clone());
+        start
+    }
+
+    // 键落在 range 内的节点摘要之和；整棵子树都在区间内时直接取缓存的摘要
+    pub fn fold_range<V: Clone + Display, S: Summary<V>, R: RangeBounds<String>>(
+        root: &Option<Rc<RefCell<BinaryNode<V, S>>>>,
+        range: R,
+    ) -> S::Item {
+        _fold(root, range.start_bound(), range.end_bound())
+    }
+
+    fn _fold<V: Clone + Display, S: Summary<V>>(
+        node: &Option<Rc<RefCell<BinaryNode<V, S>>>>,
+        lo: Bound<&String>,
+        hi: Bound<&String>,
+    ) -> S::Item {
+        let rc = match *node {
+            None => return S::identity(),
+            Some(ref rc) => rc
+        };
+        let borrow = rc.as_ref().borrow();
+        if let (Bound::Unbounded, Bound::Unbounded) = (lo, hi) {
+            return borrow.summary().clone();
+        }
+
+        let key = borrow.key();
+        let below = match lo {
+            Bound::Included(k) => key < k,
+            Bound::Excluded(k) => key <= k,
+            Bound::Unbounded => false
+        };
+        let above = match hi {
+            Bound::Included(k) => key > k,
+            Bound::Excluded(k) => key >= k,
+            Bound::Unbounded => false
+        };
+        if below {
+            return _fold(borrow.right(), lo, hi);
+        }
+        if above {
+            return _fold(borrow.left(), lo, hi);
+        }
+
+        // 当前节点在区间内：左边只受下界约束，右边只受上界约束
+        let left = _fold(borrow.left(), lo, Bound::Unbounded);
+        let right = _fold(borrow.right(), Bound::Unbounded, hi);
+        let mid = S::measure(borrow.value());
+        S::combine(&S::combine(&left, &mid), &right)
+    }
+
+    fn _replace_child<V: Clone + Display, S: Summary<V>>(
+        root: &mut Option<Rc<RefCell<BinaryNode<V, S>>>>,
+        top: Option<Rc<RefCell<BinaryNode<V, S>>>>,
+        old_child: &Rc<RefCell<BinaryNode<V, S>>>,
+        new_child: Option<Rc<RefCell<BinaryNode<V, S>>>>,
+    ) {
+        match top {
+            Some(top_rc) => {
+                if is_left_child(Rc::clone(&top_rc), Rc::clone(old_child)) {
+                    link_left(top_rc, new_child);
+                } else {
+                    link_right(top_rc, new_child);
+                }
+            }
+            None => {
+                if let Some(ref rc) = new_child {
+                    rc.as_ref().borrow_mut().set_top(None);
+                }
+                *root = new_child;
+            }
+        }
+    }
+
+    fn _find_node<V: Clone + Display, S: Summary<V>>(key: &String, mut cur: Rc<RefCell<BinaryNode<V, S>>>) -> Option<Rc<RefCell<BinaryNode<V, S>>>> {
         loop {
             let cur_t = Rc::clone(&cur);
             let cur_borrow = cur_t.as_ref().borrow();
@@ -313,7 +646,7 @@ pub mod search {
         }
     }
 
-    pub fn dumps<V: Clone + Display>(node: Rc<RefCell<BinaryNode<V>>>, level: i32) {
+    pub fn dumps<V: Clone + Display, S: Summary<V>>(node: Rc<RefCell<BinaryNode<V, S>>>, level: i32) {
         let mut idx = 0;
         while idx < level {
             print!(" ");
@@ -340,7 +673,7 @@ pub mod search {
                     }
                 },
                 borrow.key(),
-                borrow.value().as_ref().borrow(),
+                borrow.value(),
                 Rc::strong_count(&node) - 1, Rc::weak_count(&node),
                 borrow.height(),
                 borrow.depth()
@@ -384,7 +717,7 @@ pub mod avl {
     }
 
     // 判断旋转类型
-    fn _test_tran_type<V: Clone + Display>(root: Rc<RefCell<BinaryNode<V>>>) -> TranType {
+    fn _test_tran_type<V: Clone + Display, S: Summary<V>>(root: Rc<RefCell<BinaryNode<V, S>>>) -> TranType {
         let root_borrow = root.as_ref().borrow();
         let left_tree_height = root_borrow.left_height();
         let right_tree_height = root_borrow.right_height();
@@ -392,134 +725,106 @@ pub mod avl {
         if left_tree_height - right_tree_height > 1 {
             let left = root_borrow.left();
             let left_borrow = left.as_ref().unwrap().as_ref().borrow();
-            if left_borrow.left_height() > left_borrow.right_height() {
-                return TranType::SingleLeft;
+            // 删除节点后子树两侧可能等高，此时必须单旋
+            if left_borrow.left_height() >= left_borrow.right_height() {
+                TranType::SingleLeft
             } else {
-                return TranType::DualLeft;
+                TranType::DualLeft
             }
         } else if 1 < right_tree_height - left_tree_height {
             let right = root_borrow.right();
             let right_borrow = right.as_ref().unwrap().as_ref().borrow();
-            if right_borrow.left_height() < right_borrow.right_height() {
-                return TranType::SingleRight;
+            if right_borrow.left_height() <= right_borrow.right_height() {
+                TranType::SingleRight
             } else {
-                return TranType::DualRight;
+                TranType::DualRight
             }
         } else {
-            return TranType::None;
+            TranType::None
         }
     }
 
+    type NodeRc<V, S> = Rc<RefCell<BinaryNode<V, S>>>;
+
     #[derive(Getters, MutGetters, Setters, Clone, Debug)]
-    pub struct AVLTree<V: Clone + Display> {
+    pub struct AVLTree<V: Clone + Display, S: Summary<V> = ()> {
         #[set = "pub"]
         #[get_mut = "pub"]
-        root: Option<Rc<RefCell<BinaryNode<V>>>>,
+        root: Option<Rc<RefCell<BinaryNode<V, S>>>>,
     }
 
-    impl<V: Clone + Display> SearchTree<V> for AVLTree<V> {
-        fn root(&self) -> &Option<Rc<RefCell<BinaryNode<V>>>> {
-            return &self.root;
+    impl<V: Clone + Display, S: Summary<V>> SearchTree<V, S> for AVLTree<V, S> {
+        fn root(&self) -> &Option<Rc<RefCell<BinaryNode<V, S>>>> {
+            &self.root
         }
 
-        fn add_node(&mut self, node_rc: Rc<RefCell<BinaryNode<V>>>) {
-            if self.root.is_none() {
-                self.root = Some(node_rc);
-                return;
-            }
+        fn add_node(&mut self, node_rc: Rc<RefCell<BinaryNode<V, S>>>) {
+            let top = insert_node(&mut self.root, node_rc);
+            self._rebalance(top);
+        }
 
-            let mut cur = Rc::clone(self.root.as_ref().unwrap());
+        fn remove_node(&mut self, key: &String) -> Option<Rc<RefCell<BinaryNode<V, S>>>> {
+            let node = self.find_node(key)?;
+            let start = unlink_node(&mut self.root, &node);
+            self._rebalance(start);
+            Some(node)
+        }
+    }
 
-            loop {
-                let cur_p = cur.as_ptr();
-                let cur_t = Rc::clone(&cur);
-                let is_less = cur_t.as_ref().borrow().key() >= node_rc.as_ref().borrow().key();
-
-                if !is_less {
-                    if cur_t.as_ref().borrow().right().is_some() {
-                        cur = Rc::clone(cur_t.as_ref().borrow().right().as_ref().unwrap());
-                    } else {
-                        link_right(Rc::clone(&cur), Some(node_rc));
-                        break;
-                    }
-                } else {
-                    if cur_t.as_ref().borrow().left().is_some() {
-                        cur = Rc::clone(cur_t.as_ref().borrow().left().as_ref().unwrap());
-                    } else {
-                        link_left(Rc::clone(&cur), Some(node_rc));
-                        break;
-                    }
-                }
-            }
+    impl<V: Clone + Display, S: Summary<V>> Default for AVLTree<V, S> {
+        fn default() -> Self { Self::new() }
+    }
 
-            loop {
-                let t = Rc::clone(&cur);
-                let top = match t.as_ref().borrow().top() {
-                    None => None,
-                    Some(ref weak) => weak.upgrade()
-                };
-                let adjust_type = _test_tran_type(Rc::clone(&cur));
-                self._adjust(Rc::clone(&cur), adjust_type);
-                if let Some(rc) = top {
-                    cur = rc;
-                } else {
-                    break;
-                }
+    impl<V: Clone + Display, S: Summary<V>> Lookup<V> for AVLTree<V, S> {
+        fn add(&mut self, key: String, value: V) -> Option<V> {
+            if self.find_node(&key).is_some() {
+                return SearchTree::with_value_mut(self, &key, |v| mem::replace(v, value));
             }
+            SearchTree::add(self, key, value);
+            None
+        }
+
+        fn remove(&mut self, key: &String) -> Option<V> { SearchTree::remove(self, key) }
+
+        fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &String, f: F) -> Option<R> {
+            SearchTree::with_value(self, key, f)
+        }
+
+        fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, key: &String, f: F) -> Option<R> {
+            SearchTree::with_value_mut(self, key, f)
         }
     }
 
-    impl<V: Clone + Display> AVLTree<V> {
+    impl<V: Clone + Display, S: Summary<V>> AVLTree<V, S> {
         pub fn new() -> Self { Self { root: None } }
 
-        pub fn min_val(&self) -> Option<Rc<RefCell<V>>> {
-            let mut cur: Rc<RefCell<BinaryNode<V>>>;
+        // 整棵树的摘要
+        pub fn summary(&self) -> S::Item {
             match self.root {
-                None => None,
-                Some(ref r) => {
-                    cur = Rc::clone(r);
-                    loop {
-                        let cur_t = Rc::clone(&cur);
-                        let borrow_cur = cur_t.as_ref().borrow();
-                        match borrow_cur.left() {
-                            None => break Some(Rc::clone(borrow_cur.value())),
-                            Some(ref r) => cur = Rc::clone(r)
-                        }
-                    }
-                }
+                None => S::identity(),
+                Some(ref rc) => rc.as_ref().borrow().summary().clone()
             }
         }
 
+        // 键在 range 内的所有值的摘要，O(log n)
+        pub fn fold_range<R: RangeBounds<String>>(&self, range: R) -> S::Item {
+            fold_range(&self.root, range)
+        }
+
+        pub fn min_val(&self) -> Option<ValueRef<'_, V, S>> {
+            self.root.clone().map(|r| ValueRef::new(_leftmost(r)))
+        }
+
         pub fn min_val_clone(&self) -> Option<V> {
-            match self.min_val() {
-                None => None,
-                Some(rc) => Some(rc.as_ref().borrow().clone())
-            }
+            self.min_val().map(|v| v.clone())
         }
 
-        pub fn max_val(&self) -> Option<Rc<RefCell<V>>> {
-            let mut cur: Rc<RefCell<BinaryNode<V>>>;
-            match self.root {
-                None => None,
-                Some(ref r) => {
-                    cur = Rc::clone(r);
-                    loop {
-                        let cur_t = Rc::clone(&cur);
-                        let borrow_cur = cur_t.as_ref().borrow();
-                        match borrow_cur.right() {
-                            None => break Some(Rc::clone(borrow_cur.value())),
-                            Some(ref r) => cur = Rc::clone(r)
-                        }
-                    }
-                }
-            }
+        pub fn max_val(&self) -> Option<ValueRef<'_, V, S>> {
+            self.root.clone().map(|r| ValueRef::new(_rightmost(r)))
         }
 
         pub fn max_val_clone(&self) -> Option<V> {
-            match self.max_val() {
-                None => None,
-                Some(rc) => Some(rc.as_ref().borrow().clone())
-            }
+            self.max_val().map(|v| v.clone())
         }
 
         pub fn height(&self) -> i32 {
@@ -529,98 +834,411 @@ pub mod avl {
             }
         }
 
+        // 旋转前先从父节点摘下，返回父节点以及原来是否为左孩子
+        fn _take_from_top(&self, node: &Rc<RefCell<BinaryNode<V, S>>>) -> (Option<NodeRc<V, S>>, bool) {
+            let is_left = match node.as_ref().borrow().top() {
+                None => false,
+                Some(ref weak) => match weak.upgrade() {
+                    None => false,
+                    Some(top_rc) => is_left_child(top_rc, Rc::clone(node))
+                }
+            };
+            (take_from_top(node), is_left)
+        }
+
         fn _replace_parent(
             &mut self,
-            top: Option<Rc<RefCell<BinaryNode<V>>>>,
-            old_parent: Rc<RefCell<BinaryNode<V>>>,
-            new_parent: Rc<RefCell<BinaryNode<V>>>,
+            top: Option<Rc<RefCell<BinaryNode<V, S>>>>,
+            is_left: bool,
+            new_parent: Rc<RefCell<BinaryNode<V, S>>>,
         ) {
-            if top.is_some() {
-                let top_rc = top.as_ref().unwrap();
-                if is_left_child(Rc::clone(top_rc), old_parent) {
+            if let Some(ref top_rc) = top {
+                if is_left {
                     link_left(Rc::clone(top_rc), Some(new_parent));
                 } else {
                     link_right(Rc::clone(top_rc), Some(new_parent));
                 }
             } else {
+                new_parent.as_ref().borrow_mut().set_top(None);
                 self.root = Some(new_parent);
             }
         }
 
+        // 从 start 开始逐层向上检查并旋转
+        fn _rebalance(&mut self, start: Option<Rc<RefCell<BinaryNode<V, S>>>>) {
+            let mut cur = match start {
+                None => return,
+                Some(rc) => rc
+            };
+
+            loop {
+                let t = Rc::clone(&cur);
+                let top = match t.as_ref().borrow().top() {
+                    None => None,
+                    Some(ref weak) => weak.upgrade()
+                };
+                let adjust_type = _test_tran_type(Rc::clone(&cur));
+                self._adjust(Rc::clone(&cur), adjust_type);
+                if let Some(rc) = top {
+                    cur = rc;
+                } else {
+                    break;
+                }
+            }
+        }
+
         // 旋转平衡，算法参见《数据结构与算法分析：C语言描述》第二版 4.4
-        fn _adjust(&mut self, root: Rc<RefCell<BinaryNode<V>>>, t: TranType) {
+        fn _adjust(&mut self, root: Rc<RefCell<BinaryNode<V, S>>>, t: TranType) {
             match t {
                 TranType::SingleRight => {
-                    println!("SingleRight!");
-                    let mut k1 = Rc::clone(&root);
-                    let mut k2 = link_right(Rc::clone(&k1), None).unwrap();
-                    let mut y = link_left(Rc::clone(&k2), None);
-                    let top = take_from_top(&root);
+                    let k1 = Rc::clone(&root);
+                    let k2 = link_right(Rc::clone(&k1), None).unwrap();
+                    let y = link_left(Rc::clone(&k2), None);
+                    let (top, is_left) = self._take_from_top(&root);
 
                     link_right(Rc::clone(&k1), y);
-                    link_left(Rc::clone(&k2), Some(k1));
+                    link_left(Rc::clone(&k2), Some(Rc::clone(&k1)));
 
-                    self._replace_parent(top, root, k2);
+                    self._replace_parent(top, is_left, k2);
                 }
                 TranType::DualRight => {
-                    println!("DualRight!");
-                    let mut k1 = Rc::clone(&root);
-                    let mut k3 = link_left(Rc::clone(&k1), None).unwrap();
-                    let mut k2 = link_right(Rc::clone(&k3), None).unwrap();
-                    let mut b = link_left(Rc::clone(&k2), None);
-                    let mut c = link_right(Rc::clone(&k2), None);
-                    let top = take_from_top(&root);
+                    let k1 = Rc::clone(&root);
+                    let k3 = link_right(Rc::clone(&k1), None).unwrap();
+                    let k2 = link_left(Rc::clone(&k3), None).unwrap();
+                    let b = link_left(Rc::clone(&k2), None);
+                    let c = link_right(Rc::clone(&k2), None);
+                    let (top, is_left) = self._take_from_top(&root);
 
                     link_right(Rc::clone(&k1), b);
                     link_left(Rc::clone(&k3), c);
-                    link_right(Rc::clone(&k2), Some(k3));
-                    link_left(Rc::clone(&k2), Some(k1));
+                    link_right(Rc::clone(&k2), Some(Rc::clone(&k3)));
+                    link_left(Rc::clone(&k2), Some(Rc::clone(&k1)));
 
-                    self._replace_parent(top, root, k2);
+                    self._replace_parent(top, is_left, k2);
                 }
                 TranType::SingleLeft => {
-                    println!("SingleLeft!");
-                    let mut k2 = Rc::clone(&root);
-                    let mut k1 = link_left(Rc::clone(&k2), None).unwrap();
-                    let mut y = link_left(Rc::clone(&k1), None);
-                    let top = take_from_top(&root);
+                    let k2 = Rc::clone(&root);
+                    let k1 = link_left(Rc::clone(&k2), None).unwrap();
+                    let y = link_right(Rc::clone(&k1), None);
+                    let (top, is_left) = self._take_from_top(&root);
 
                     link_left(Rc::clone(&k2), y);
-                    link_right(Rc::clone(&k1), Some(k2));
+                    link_right(Rc::clone(&k1), Some(Rc::clone(&k2)));
 
-                    self._replace_parent(top, root, k1);
+                    self._replace_parent(top, is_left, k1);
                 }
                 TranType::DualLeft => {
-                    println!("DualLeft!");
-                    let mut k3 = Rc::clone(&root);
-                    let mut k1 = link_left(Rc::clone(&k3), None).unwrap();
-                    let mut k2 = link_right(Rc::clone(&k1), None).unwrap();
-                    let mut b = link_left(Rc::clone(&k2), None);
-                    let mut c = link_right(Rc::clone(&k2), None);
-                    let top = take_from_top(&root);
+                    let k3 = Rc::clone(&root);
+                    let k1 = link_left(Rc::clone(&k3), None).unwrap();
+                    let k2 = link_right(Rc::clone(&k1), None).unwrap();
+                    let b = link_left(Rc::clone(&k2), None);
+                    let c = link_right(Rc::clone(&k2), None);
+                    let (top, is_left) = self._take_from_top(&root);
 
                     link_right(Rc::clone(&k1), b);
                     link_left(Rc::clone(&k3), c);
-                    link_right(Rc::clone(&k2), Some(k3));
-                    link_left(Rc::clone(&k2), Some(k1));
+                    link_right(Rc::clone(&k2), Some(Rc::clone(&k3)));
+                    link_left(Rc::clone(&k2), Some(Rc::clone(&k1)));
+
+                    self._replace_parent(top, is_left, k2);
+                }
+                TranType::None => {}
+            }
+        }
+    }
+
+    fn _top_of<V: Clone + Display, S: Summary<V>>(node: &NodeRc<V, S>) -> Option<NodeRc<V, S>> {
+        node.as_ref().borrow().top().as_ref().and_then(|weak| weak.upgrade())
+    }
+
+    fn _leftmost<V: Clone + Display, S: Summary<V>>(mut node: NodeRc<V, S>) -> NodeRc<V, S> {
+        loop {
+            let left = node.as_ref().borrow().left().clone();
+            match left {
+                None => return node,
+                Some(rc) => node = rc
+            }
+        }
+    }
+
+    fn _rightmost<V: Clone + Display, S: Summary<V>>(mut node: NodeRc<V, S>) -> NodeRc<V, S> {
+        loop {
+            let right = node.as_ref().borrow().right().clone();
+            match right {
+                None => return node,
+                Some(rc) => node = rc
+            }
+        }
+    }
+
+    // 中序后继：有右子树时取右子树最左节点，否则沿 top 向上，直到从某个节点的左边上来
+    fn _next<V: Clone + Display, S: Summary<V>>(node: &NodeRc<V, S>) -> Option<NodeRc<V, S>> {
+        let right = node.as_ref().borrow().right().clone();
+        if let Some(rc) = right {
+            return Some(_leftmost(rc));
+        }
+        let mut cur = Rc::clone(node);
+        loop {
+            let top = _top_of(&cur)?;
+            if is_left_child(Rc::clone(&top), Rc::clone(&cur)) {
+                return Some(top);
+            }
+            cur = top;
+        }
+    }
+
+    fn _prev<V: Clone + Display, S: Summary<V>>(node: &NodeRc<V, S>) -> Option<NodeRc<V, S>> {
+        let left = node.as_ref().borrow().left().clone();
+        if let Some(rc) = left {
+            return Some(_rightmost(rc));
+        }
+        let mut cur = Rc::clone(node);
+        loop {
+            let top = _top_of(&cur)?;
+            if !is_left_child(Rc::clone(&top), Rc::clone(&cur)) {
+                return Some(top);
+            }
+            cur = top;
+        }
+    }
+
+    // 第一个键不小于 key 的节点
+    fn _lower_bound<V: Clone + Display, S: Summary<V>>(root: &Option<NodeRc<V, S>>, key: &str) -> Option<NodeRc<V, S>> {
+        let mut found = None;
+        let mut cur = root.clone();
+        while let Some(rc) = cur {
+            let ge = rc.as_ref().borrow().key().as_str() >= key;
+            cur = if ge { rc.as_ref().borrow().left().clone() } else { rc.as_ref().borrow().right().clone() };
+            if ge {
+                found = Some(rc);
+            }
+        }
+        found
+    }
+
+    // 游标位置：None 表示幽灵位置
+    type Position<V, S> = Option<NodeRc<V, S>>;
+
+    // 走到最后一个节点之后进入幽灵位置，再走回到第一个节点
+    fn _move_next<V: Clone + Display, S: Summary<V>>(root: &Option<NodeRc<V, S>>, cur: &mut Position<V, S>) {
+        let next = match cur.take() {
+            None => root.clone().map(_leftmost),
+            Some(rc) => _next(&rc),
+        };
+        *cur = next;
+    }
+
+    fn _move_prev<V: Clone + Display, S: Summary<V>>(root: &Option<NodeRc<V, S>>, cur: &mut Position<V, S>) {
+        let prev = match cur.take() {
+            None => root.clone().map(_rightmost),
+            Some(rc) => _prev(&rc),
+        };
+        *cur = prev;
+    }
+
+    // 只读游标，按键的顺序在树上移动
+    pub struct Cursor<'a, V: Clone + Display + 'a, S: Summary<V> + 'a = ()> {
+        tree: &'a AVLTree<V, S>,
+        cur: Position<V, S>,
+    }
+
+    impl<'a, V: Clone + Display, S: Summary<V>> Cursor<'a, V, S> {
+        pub fn key(&self) -> Option<Ref<'_, String>> {
+            self.cur.as_ref().map(|rc| Ref::map(rc.as_ref().borrow(), |n| n.key()))
+        }
+
+        pub fn value(&self) -> Option<Ref<'_, V>> {
+            self.cur.as_ref().map(|rc| Ref::map(rc.as_ref().borrow(), |n| n.value()))
+        }
+
+        pub fn move_next(&mut self) { _move_next(&self.tree.root, &mut self.cur) }
+
+        pub fn move_prev(&mut self) { _move_prev(&self.tree.root, &mut self.cur) }
+    }
+
+    // 可修改的游标：删除当前节点或在它之后插入，树保持平衡
+    pub struct CursorMut<'a, V: Clone + Display + 'a, S: Summary<V> + 'a = ()> {
+        tree: &'a mut AVLTree<V, S>,
+        cur: Position<V, S>,
+    }
 
-                    self._replace_parent(top, root, k2);
+    impl<'a, V: Clone + Display, S: Summary<V>> CursorMut<'a, V, S> {
+        pub fn key(&self) -> Option<Ref<'_, String>> {
+            self.cur.as_ref().map(|rc| Ref::map(rc.as_ref().borrow(), |n| n.key()))
+        }
+
+        pub fn value(&self) -> Option<Ref<'_, V>> {
+            self.cur.as_ref().map(|rc| Ref::map(rc.as_ref().borrow(), |n| n.value()))
+        }
+
+        pub fn move_next(&mut self) { _move_next(&self.tree.root, &mut self.cur) }
+
+        pub fn move_prev(&mut self) { _move_prev(&self.tree.root, &mut self.cur) }
+
+        // 修改当前值后沿 top 刷新摘要
+        pub fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, f: F) -> Option<R> {
+            let rc = self.cur.as_ref()?;
+            let r = f(rc.as_ref().borrow_mut().value_mut());
+            refresh_up(Some(Rc::clone(rc)));
+            Some(r)
+        }
+
+        // 删除当前节点并返回它的值，游标移到后继；旋转不改变节点身份，事先取得的后继仍然有效
+        pub fn remove_current(&mut self) -> Option<V> {
+            let rc = self.cur.take()?;
+            let next = _next(&rc);
+            let start = unlink_node(&mut self.tree.root, &rc);
+            self.tree._rebalance(start);
+            self.cur = next;
+            Some(take_node_value(rc))
+        }
+
+        // 在当前节点之后插入，key 必须落在当前键与后继键之间；位于幽灵位置时插入到最前面
+        // 游标停留在原来的位置
+        pub fn insert_after(&mut self, key: String, value: V) -> Result<(), String> {
+            let next = match self.cur {
+                None => self.tree.root.clone().map(_leftmost),
+                Some(ref rc) => _next(rc),
+            };
+            let after_cur = self.cur.as_ref().is_none_or(|rc| *rc.as_ref().borrow().key() <= key);
+            let before_next = next.as_ref().is_none_or(|rc| key <= *rc.as_ref().borrow().key());
+            if !after_cur || !before_next {
+                return Err(format!("key={} is out of order", key));
+            }
+
+            let node_rc = Rc::new(RefCell::new(BinaryNode::with_summary(key, value)));
+            // 新节点成为中序意义上当前节点的下一个：当前节点没有右孩子时挂在它右边，否则挂在后继的左边
+            let parent = match self.cur {
+                Some(ref rc) if rc.as_ref().borrow().right().is_none() => {
+                    link_right(Rc::clone(rc), Some(node_rc));
+                    Some(Rc::clone(rc))
                 }
-                _ => ()
+                // 此时 next 是当前节点右子树或整棵树的最左节点，没有左孩子
+                _ => match next {
+                    None => {
+                        self.tree.root = Some(node_rc);
+                        None
+                    }
+                    Some(rc) => {
+                        link_left(Rc::clone(&rc), Some(node_rc));
+                        Some(rc)
+                    }
+                },
+            };
+            refresh_up(parent.clone());
+            self.tree._rebalance(parent);
+            Ok(())
+        }
+    }
+
+    impl<V: Clone + Display, S: Summary<V>> AVLTree<V, S> {
+        pub fn cursor_front(&self) -> Cursor<'_, V, S> {
+            Cursor { tree: self, cur: self.root.clone().map(_leftmost) }
+        }
+
+        pub fn cursor_back(&self) -> Cursor<'_, V, S> {
+            Cursor { tree: self, cur: self.root.clone().map(_rightmost) }
+        }
+
+        // 定位到第一个键不小于 key 的节点，不存在时位于幽灵位置
+        pub fn lower_bound(&self, key: &str) -> Cursor<'_, V, S> {
+            Cursor { tree: self, cur: _lower_bound(&self.root, key) }
+        }
+
+        pub fn cursor_front_mut(&mut self) -> CursorMut<'_, V, S> {
+            let cur = self.root.clone().map(_leftmost);
+            CursorMut { tree: self, cur }
+        }
+
+        pub fn cursor_back_mut(&mut self) -> CursorMut<'_, V, S> {
+            let cur = self.root.clone().map(_rightmost);
+            CursorMut { tree: self, cur }
+        }
+
+        pub fn lower_bound_mut(&mut self, key: &str) -> CursorMut<'_, V, S> {
+            let cur = _lower_bound(&self.root, key);
+            CursorMut { tree: self, cur }
+        }
+    }
+}
+
+pub mod bst {
+    use super::*;
+    use super::binary::*;
+    use super::search::*;
+
+    // 不做任何平衡的二叉查找树，用来和 AVLTree 对比
+    #[derive(Getters, MutGetters, Setters, Clone, Debug)]
+    pub struct PlainBst<V: Clone + Display, S: Summary<V> = ()> {
+        #[set = "pub"]
+        #[get_mut = "pub"]
+        root: Option<Rc<RefCell<BinaryNode<V, S>>>>,
+    }
+
+    impl<V: Clone + Display, S: Summary<V>> SearchTree<V, S> for PlainBst<V, S> {
+        fn root(&self) -> &Option<Rc<RefCell<BinaryNode<V, S>>>> {
+            &self.root
+        }
+
+        fn add_node(&mut self, node_rc: Rc<RefCell<BinaryNode<V, S>>>) {
+            insert_node(&mut self.root, node_rc);
+        }
+
+        fn remove_node(&mut self, key: &String) -> Option<Rc<RefCell<BinaryNode<V, S>>>> {
+            let node = self.find_node(key)?;
+            unlink_node(&mut self.root, &node);
+            Some(node)
+        }
+    }
+
+    impl<V: Clone + Display, S: Summary<V>> Default for PlainBst<V, S> {
+        fn default() -> Self { Self::new() }
+    }
+
+    impl<V: Clone + Display, S: Summary<V>> Lookup<V> for PlainBst<V, S> {
+        fn add(&mut self, key: String, value: V) -> Option<V> {
+            if self.find_node(&key).is_some() {
+                return SearchTree::with_value_mut(self, &key, |v| mem::replace(v, value));
             }
+            SearchTree::add(self, key, value);
+            None
+        }
+
+        fn remove(&mut self, key: &String) -> Option<V> { SearchTree::remove(self, key) }
+
+        fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &String, f: F) -> Option<R> {
+            SearchTree::with_value(self, key, f)
+        }
+
+        fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, key: &String, f: F) -> Option<R> {
+            SearchTree::with_value_mut(self, key, f)
         }
     }
+
+    impl<V: Clone + Display, S: Summary<V>> PlainBst<V, S> {
+        pub fn new() -> Self { Self { root: None } }
+    }
 }
 
+pub mod arena;
+pub mod audit;
+pub mod boxed;
+pub mod memory;
+pub mod persistent;
+pub mod mvcc;
+pub mod sync_avl;
+#[cfg(feature = "raw-avl")]
+pub mod raw;
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use testing::Lcg;
     use tree::search::SearchTree;
 
     #[test]
     fn binary_node() {
         use super::binary::*;
-        use super::search::*;
 
         let a = Rc::new(RefCell::new(BinaryNode::new(String::from("28"), "Tony")));
         let b = Rc::new(RefCell::new(BinaryNode::new(String::from("20"), "Guo")));
@@ -652,7 +1270,6 @@ mod tests {
     fn link_test() {
         use super::*;
         use super::binary::*;
-        use super::search::*;
 
         let a = Rc::new(RefCell::new(BinaryNode::new(String::from("28"), "Tony")));
         let b = Rc::new(RefCell::new(BinaryNode::new(String::from("20"), "Guo")));
@@ -704,7 +1321,7 @@ mod tests {
         let mut idx = 0;
         let mut tree = AVLTree::<String>::new();
 
-        while names.len() > 0 {
+        while !names.is_empty() {
             tree.add(idx.to_string(), String::from(names.pop().unwrap()));
             idx += 1;
         }
@@ -714,4 +1331,238 @@ mod tests {
         assert_eq!("4444", tree.min_val_clone().unwrap());
         assert_eq!("9953", tree.max_val_clone().unwrap());
     }
-}
\ No newline at end of file
+
+    // 中序遍历收集 key，同时检查父指针和（可选）平衡条件
+    fn check_tree(node: &Option<Rc<RefCell<binary::BinaryNode<i32>>>>, balanced: bool, keys: &mut Vec<String>) {
+        if let Some(ref rc) = *node {
+            let borrow = rc.as_ref().borrow();
+            for child in [borrow.left(), borrow.right()].iter() {
+                if let Some(ref c) = **child {
+                    let top = c.as_ref().borrow().top().as_ref().unwrap().upgrade().unwrap();
+                    assert!(Rc::ptr_eq(&top, rc));
+                }
+            }
+            if balanced {
+                assert!((borrow.left_height() - borrow.right_height()).abs() <= 1);
+            }
+            check_tree(borrow.left(), balanced, keys);
+            keys.push(borrow.key().clone());
+            check_tree(borrow.right(), balanced, keys);
+        }
+    }
+
+    #[test]
+    fn plain_bst_vs_avl() {
+        use super::avl::AVLTree;
+        use super::bst::PlainBst;
+
+        let mut plain = PlainBst::<i32>::new();
+        let mut avl = AVLTree::<i32>::new();
+        for i in 0..127 {
+            plain.add(format!("{:03}", i), i);
+            avl.add(format!("{:03}", i), i);
+        }
+        // 顺序插入时普通二叉查找树退化成链表
+        assert_eq!(126, SearchTree::height(&plain));
+        assert_eq!(6, avl.height());
+        assert_eq!(Some(64), plain.find_and_clone(&String::from("064")));
+
+        let mut plain = PlainBst::<i32>::new();
+        let mut rng = Lcg::new(7);
+        for i in 0..127 {
+            let seed = rng.next_u32();
+            plain.add(format!("{:05}", seed % 100000), i);
+        }
+        assert!(SearchTree::height(&plain) < 126);
+        assert!(SearchTree::height(&plain) > 6);
+    }
+
+    #[test]
+    fn remove_node() {
+        use super::avl::AVLTree;
+        use super::bst::PlainBst;
+
+        let mut plain = PlainBst::<i32>::new();
+        let mut avl = AVLTree::<i32>::new();
+        let mut rng = Lcg::new(11);
+        let mut expect = Vec::new();
+        for i in 0..200 {
+            let seed = rng.next_u32();
+            let key = format!("{:04}", (seed >> 8) % 10000);
+            if expect.contains(&key) {
+                continue;
+            }
+            plain.add(key.clone(), i);
+            avl.add(key.clone(), i);
+            expect.push(key);
+        }
+
+        for (i, key) in expect.clone().iter().enumerate().filter(|&(i, _)| i % 2 == 0) {
+            let v = avl.remove(key).unwrap();
+            assert_eq!(plain.remove(key).unwrap(), v);
+            assert!(avl.get(key).is_none());
+            expect.retain(|k| k != key);
+
+            if i % 10 == 0 {
+                let mut keys = Vec::new();
+                check_tree(avl.root(), true, &mut keys);
+                expect.sort();
+                assert_eq!(expect, keys);
+                let mut keys = Vec::new();
+                check_tree(plain.root(), false, &mut keys);
+                assert_eq!(expect, keys);
+            }
+        }
+        assert!(avl.remove(&String::from("x")).is_none());
+
+        for key in expect.iter() {
+            assert!(avl.remove(key).is_some());
+            assert!(plain.remove(key).is_some());
+        }
+        assert!(avl.root().is_none());
+        assert!(plain.root().is_none());
+    }
+
+    #[test]
+    fn fold_range_summary() {
+        use super::avl::AVLTree;
+        use aggregate::{Count, Max, Sum};
+
+        let mut sum = AVLTree::<i64, Sum>::new();
+        let mut count = AVLTree::<i64, Count>::new();
+        let mut max = AVLTree::<i64, Max>::new();
+        let mut model = Vec::new();
+        let mut rng = Lcg::new(3);
+        for _ in 0..150 {
+            let seed = rng.next_u32();
+            let key = format!("{:03}", (seed >> 8) % 1000);
+            if model.iter().any(|(k, _)| *k == key) {
+                continue;
+            }
+            let v = i64::from(seed >> 20) - 2000;
+            sum.add(key.clone(), v);
+            count.add(key.clone(), v);
+            max.add(key.clone(), v);
+            model.push((key, v));
+        }
+        for (key, _) in model.drain(..50).collect::<Vec<_>>() {
+            sum.remove(&key);
+            count.remove(&key);
+            max.remove(&key);
+        }
+        for &mut (ref key, ref mut v) in model.iter_mut().take(20) {
+            *v += 7;
+            sum.update(key, *v).unwrap();
+            max.update(key, *v).unwrap();
+        }
+
+        assert_eq!(model.iter().map(|&(_, v)| v).sum::<i64>(), sum.summary());
+        assert_eq!(model.len(), count.summary());
+        for &(lo, hi) in [("100", "500"), ("000", "999"), ("250", "251"), ("700", "300")].iter() {
+            let (lo, hi) = (String::from(lo), String::from(hi));
+            let inside: Vec<i64> = model.iter().filter(|&(k, _)| *k >= lo && *k < hi).map(|&(_, v)| v).collect();
+            assert_eq!(inside.iter().sum::<i64>(), sum.fold_range(lo.clone()..hi.clone()));
+            assert_eq!(inside.len(), count.fold_range(lo.clone()..hi.clone()));
+            assert_eq!(inside.iter().cloned().max().unwrap_or(i64::MIN), max.fold_range(lo.clone()..hi.clone()));
+        }
+        let hi = String::from("500");
+        let inside = model.iter().filter(|&(k, _)| *k <= hi).count();
+        assert_eq!(inside, count.fold_range(..=hi));
+    }
+
+    #[test]
+    fn cursor_walk_and_edit() {
+        use super::avl::AVLTree;
+
+        let mut tree = AVLTree::<i32>::new();
+        for i in 0..40 {
+            tree.add(format!("{:03}", i * 3), i);
+        }
+        let mut c = tree.cursor_front();
+        let mut forward = Vec::new();
+        while let Some(k) = c.key().map(|k| k.clone()) {
+            forward.push(k);
+            c.move_next();
+        }
+        // 幽灵位置之后回到开头
+        c.move_next();
+        assert_eq!(Some(String::from("000")), c.key().map(|k| k.clone()));
+        drop(c);
+        let mut c = tree.cursor_back();
+        let mut backward = Vec::new();
+        while let Some(v) = c.value().map(|v| *v) {
+            backward.push(v);
+            c.move_prev();
+        }
+        assert_eq!((0..40).map(|i| format!("{:03}", i * 3)).collect::<Vec<_>>(), forward);
+        assert_eq!((0..40).rev().collect::<Vec<_>>(), backward);
+        assert_eq!(Some(14), tree.lower_bound("040").value().map(|v| *v));
+        assert!(tree.lower_bound("99").key().is_none());
+
+        // 边看边删：删掉所有奇数值，再在每个剩余节点之后插入一个键
+        let mut c = tree.lower_bound_mut("");
+        while let Some(v) = c.value().map(|v| *v) {
+            if v % 2 == 1 {
+                assert_eq!(Some(v), c.remove_current());
+            } else {
+                let key = format!("{:03}+", v * 3);
+                c.insert_after(key, -v).unwrap();
+                assert!(c.insert_after(String::from("zz"), 0).is_err());
+                c.move_next();
+                c.move_next();
+            }
+        }
+        let mut c = tree.cursor_front_mut();
+        c.move_prev();
+        assert!(c.insert_after(String::from("01"), 0).is_err());
+        c.insert_after(String::from(""), 100).unwrap();
+        c.move_next();
+        assert_eq!(Some(()), c.with_value_mut(|v| *v *= 2));
+        // 游标持有当前节点的 Rc，释放后树里不再有外部句柄
+        drop(c);
+        assert_eq!(Some(200), tree.find_and_clone(&String::new()));
+
+        let mut keys = Vec::new();
+        check_tree(tree.root(), true, &mut keys);
+        let mut expect = vec![String::new()];
+        for i in (0..40).filter(|i| i % 2 == 0) {
+            expect.push(format!("{:03}", i * 3));
+            expect.push(format!("{:03}+", i * 3));
+        }
+        assert_eq!(expect, keys);
+        assert!(tree.audit().is_clean());
+    }
+
+    #[test]
+    fn with_value_borrows_in_place() {
+        use super::avl::AVLTree;
+        use aggregate::Sum;
+
+        let mut tree = AVLTree::<i64, Sum>::new();
+        for i in 0..20 {
+            tree.add(format!("{:02}", i), i);
+        }
+        assert_eq!(Some(14), tree.with_value(&String::from("07"), |v| v * 2));
+        assert_eq!(None, tree.with_value(&String::from("99"), |v| *v));
+
+        // 原地修改后摘要随之更新，且没有句柄留在外面
+        assert_eq!(Some(()), tree.with_value_mut(&String::from("07"), |v| *v += 100));
+        assert_eq!(Some(107), tree.find_and_clone(&String::from("07")));
+        assert_eq!((0..20).sum::<i64>() + 100, tree.summary());
+        assert!(tree.audit().is_clean());
+        assert_eq!(None, tree.with_value_mut(&String::from("99"), |v| *v += 1));
+
+        // 守卫借出值本身，可写守卫释放时刷新摘要
+        assert_eq!(107, *tree.get(&String::from("07")).unwrap());
+        assert_eq!((0, 19), (*tree.min_val().unwrap(), *tree.max_val().unwrap()));
+        *tree.get_mut(&String::from("19")).unwrap() -= 19;
+        assert_eq!((0..20).sum::<i64>() + 81, tree.summary());
+        assert!(tree.get_mut(&String::from("99")).is_none());
+        let a = tree.get(&String::from("00")).unwrap();
+        let b = tree.get(&String::from("00")).unwrap();
+        assert_eq!(*a + *b, 0);
+        drop((a, b));
+        assert_eq!(Some(5), tree.remove(&String::from("05")));
+        assert!(tree.audit().is_clean());
+    }
+}
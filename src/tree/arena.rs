@@ -0,0 +1,415 @@
+// 节点存放在 Vec 中、用下标互相引用的 AVL 树：没有引用计数和借用标记，删除的槽位进入空闲链表复用
+use std::cmp::Ordering;
+use std::mem::size_of;
+
+use super::memory::{HeapSize, MemoryUsage};
+use super::search::Lookup;
+
+struct ArenaNode<V> {
+    key: String,
+    value: V,
+    left: Option<usize>,
+    right: Option<usize>,
+    // 叶子为 0，与 BinaryNode::height 一致
+    height: i32,
+}
+
+pub struct ArenaAvl<V> {
+    nodes: Vec<Option<ArenaNode<V>>>,
+    free: Vec<usize>,
+    root: Option<usize>,
+    len: usize,
+}
+
+impl<V> Default for ArenaAvl<V> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<V> ArenaAvl<V> {
+    pub fn new() -> Self { Self::with_capacity(0) }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { nodes: Vec::with_capacity(capacity), free: Vec::new(), root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    // 已分配的槽位数，包括空闲槽位
+    pub fn slots(&self) -> usize { self.nodes.len() }
+
+    pub fn height(&self) -> i32 { self._height(self.root) }
+
+    pub fn find(&self, key: &str) -> Option<&V> {
+        self._find(key).map(|i| &self.node(i).value)
+    }
+
+    pub fn find_mut(&mut self, key: &str) -> Option<&mut V> {
+        let i = self._find(key)?;
+        Some(&mut self.node_mut(i).value)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool { self._find(key).is_some() }
+
+    // 插入，key 已存在时替换并返回旧值
+    pub fn add(&mut self, key: String, value: V) -> Option<V> {
+        let (root, old) = self._insert(self.root, key, value);
+        self.root = Some(root);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn update(&mut self, key: &str, value: V) -> Result<(), String> {
+        match self.find_mut(key) {
+            None => Err(format!("node={} not exists", key)),
+            Some(v) => {
+                *v = value;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let (root, removed) = self._remove(self.root, key);
+        self.root = root;
+        let i = removed?;
+        self.len -= 1;
+        Some(self.release(i).value)
+    }
+
+    pub fn min_val(&self) -> Option<&V> {
+        let mut i = self.root?;
+        while let Some(l) = self.node(i).left {
+            i = l;
+        }
+        Some(&self.node(i).value)
+    }
+
+    pub fn max_val(&self) -> Option<&V> {
+        let mut i = self.root?;
+        while let Some(r) = self.node(i).right {
+            i = r;
+        }
+        Some(&self.node(i).value)
+    }
+
+    // 中序遍历
+    pub fn iter(&self) -> Iter<'_, V> {
+        let mut it = Iter { tree: self, stack: Vec::new() };
+        it.push_left(self.root);
+        it
+    }
+
+    fn node(&self, i: usize) -> &ArenaNode<V> {
+        self.nodes[i].as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, i: usize) -> &mut ArenaNode<V> {
+        self.nodes[i].as_mut().unwrap()
+    }
+
+    fn alloc(&mut self, node: ArenaNode<V>) -> usize {
+        match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = Some(node);
+                i
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn release(&mut self, i: usize) -> ArenaNode<V> {
+        self.free.push(i);
+        self.nodes[i].take().unwrap()
+    }
+
+    fn _find(&self, key: &str) -> Option<usize> {
+        let mut cur = self.root;
+        while let Some(i) = cur {
+            let n = self.node(i);
+            cur = match key.cmp(&n.key) {
+                Ordering::Less => n.left,
+                Ordering::Greater => n.right,
+                Ordering::Equal => return Some(i),
+            };
+        }
+        None
+    }
+
+    fn _height(&self, i: Option<usize>) -> i32 {
+        i.map_or(-1, |i| self.node(i).height)
+    }
+
+    fn _fix_height(&mut self, i: usize) {
+        let h = 1 + self._height(self.node(i).left).max(self._height(self.node(i).right));
+        self.node_mut(i).height = h;
+    }
+
+    fn _balance_factor(&self, i: usize) -> i32 {
+        self._height(self.node(i).left) - self._height(self.node(i).right)
+    }
+
+    // 右旋，返回新的子树根
+    fn _rotate_right(&mut self, i: usize) -> usize {
+        let l = self.node(i).left.unwrap();
+        self.node_mut(i).left = self.node(l).right;
+        self.node_mut(l).right = Some(i);
+        self._fix_height(i);
+        self._fix_height(l);
+        l
+    }
+
+    fn _rotate_left(&mut self, i: usize) -> usize {
+        let r = self.node(i).right.unwrap();
+        self.node_mut(i).right = self.node(r).left;
+        self.node_mut(r).left = Some(i);
+        self._fix_height(i);
+        self._fix_height(r);
+        r
+    }
+
+    // 更新高度并在失衡时旋转，返回新的子树根
+    fn _rebalance(&mut self, i: usize) -> usize {
+        self._fix_height(i);
+        let bf = self._balance_factor(i);
+        if bf > 1 {
+            let l = self.node(i).left.unwrap();
+            if self._balance_factor(l) < 0 {
+                let nl = self._rotate_left(l);
+                self.node_mut(i).left = Some(nl);
+            }
+            self._rotate_right(i)
+        } else if bf < -1 {
+            let r = self.node(i).right.unwrap();
+            if self._balance_factor(r) > 0 {
+                let nr = self._rotate_right(r);
+                self.node_mut(i).right = Some(nr);
+            }
+            self._rotate_left(i)
+        } else {
+            i
+        }
+    }
+
+    fn _insert(&mut self, cur: Option<usize>, key: String, value: V) -> (usize, Option<V>) {
+        let i = match cur {
+            None => {
+                let node = ArenaNode { key, value, left: None, right: None, height: 0 };
+                return (self.alloc(node), None);
+            }
+            Some(i) => i,
+        };
+        let old = match key.cmp(&self.node(i).key) {
+            Ordering::Equal => return (i, Some(::std::mem::replace(&mut self.node_mut(i).value, value))),
+            Ordering::Less => {
+                let (l, old) = self._insert(self.node(i).left, key, value);
+                self.node_mut(i).left = Some(l);
+                old
+            }
+            Ordering::Greater => {
+                let (r, old) = self._insert(self.node(i).right, key, value);
+                self.node_mut(i).right = Some(r);
+                old
+            }
+        };
+        // 替换旧值时形状不变，无需调整
+        if old.is_some() {
+            return (i, old);
+        }
+        (self._rebalance(i), None)
+    }
+
+    // 摘下 key 对应的节点，返回新的子树根和被摘下的下标（尚未释放）
+    fn _remove(&mut self, cur: Option<usize>, key: &str) -> (Option<usize>, Option<usize>) {
+        let i = match cur {
+            None => return (None, None),
+            Some(i) => i,
+        };
+        let removed = match key.cmp(&self.node(i).key) {
+            Ordering::Less => {
+                let (l, removed) = self._remove(self.node(i).left, key);
+                self.node_mut(i).left = l;
+                removed
+            }
+            Ordering::Greater => {
+                let (r, removed) = self._remove(self.node(i).right, key);
+                self.node_mut(i).right = r;
+                removed
+            }
+            Ordering::Equal => {
+                let (left, right) = (self.node(i).left, self.node(i).right);
+                let replace = match (left, right) {
+                    (None, _) => right,
+                    (_, None) => left,
+                    // 两个孩子时用后继节点顶替当前位置
+                    (Some(_), Some(r)) => {
+                        let (r, succ) = self._remove_min(r);
+                        let n = self.node_mut(succ);
+                        n.left = left;
+                        n.right = r;
+                        Some(self._rebalance(succ))
+                    }
+                };
+                return (replace, Some(i));
+            }
+        };
+        if removed.is_none() {
+            return (Some(i), None);
+        }
+        (Some(self._rebalance(i)), removed)
+    }
+
+    // 摘下子树中最小的节点，返回新的子树根和最小节点的下标
+    fn _remove_min(&mut self, i: usize) -> (Option<usize>, usize) {
+        match self.node(i).left {
+            None => {
+                let r = self.node_mut(i).right.take();
+                (r, i)
+            }
+            Some(l) => {
+                let (l, min) = self._remove_min(l);
+                self.node_mut(i).left = l;
+                (Some(self._rebalance(i)), min)
+            }
+        }
+    }
+}
+
+impl<V: Clone> ArenaAvl<V> {
+    pub fn find_and_clone(&self, key: &str) -> Option<V> { self.find(key).cloned() }
+
+    pub fn min_val_clone(&self) -> Option<V> { self.min_val().cloned() }
+
+    pub fn max_val_clone(&self) -> Option<V> { self.max_val().cloned() }
+}
+
+#[allow(clippy::ptr_arg)]
+impl<V: Clone> Lookup<V> for ArenaAvl<V> {
+    fn add(&mut self, key: String, value: V) -> Option<V> { ArenaAvl::add(self, key, value) }
+
+    fn remove(&mut self, key: &String) -> Option<V> { ArenaAvl::remove(self, key) }
+
+    fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &String, f: F) -> Option<R> { self.find(key).map(f) }
+
+    fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, key: &String, f: F) -> Option<R> {
+        self.find_mut(key).map(f)
+    }
+}
+
+impl<V: HeapSize> ArenaAvl<V> {
+    // 槽位按 Vec 的容量计算，空闲槽位与空闲链表也算在节点开销里
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage {
+            nodes: self.len,
+            node_bytes: self.nodes.capacity() * size_of::<Option<ArenaNode<V>>>() + self.free.capacity() * size_of::<usize>(),
+            ..MemoryUsage::default()
+        };
+        for n in self.nodes.iter().flatten() {
+            usage.key_bytes += n.key.capacity();
+            usage.value_bytes += n.value.heap_size();
+        }
+        usage
+    }
+}
+
+pub struct Iter<'a, V: 'a> {
+    tree: &'a ArenaAvl<V>,
+    stack: Vec<usize>,
+}
+
+impl<'a, V> Iter<'a, V> {
+    fn push_left(&mut self, mut cur: Option<usize>) {
+        while let Some(i) = cur {
+            self.stack.push(i);
+            cur = self.tree.node(i).left;
+        }
+    }
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (&'a String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let i = self.stack.pop()?;
+        let tree = self.tree;
+        let n = tree.node(i);
+        self.push_left(n.right);
+        Some((&n.key, &n.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::check_against_model;
+    use tree::avl::AVLTree;
+
+    // 校验有序、平衡、高度缓存正确，返回子树高度
+    fn check<V>(tree: &ArenaAvl<V>, cur: Option<usize>, lo: Option<&str>, hi: Option<&str>) -> i32 {
+        let i = match cur {
+            None => return -1,
+            Some(i) => i,
+        };
+        let n = tree.node(i);
+        assert!(lo.is_none_or(|lo| lo < n.key.as_str()));
+        assert!(hi.is_none_or(|hi| n.key.as_str() < hi));
+        let l = check(tree, n.left, lo, Some(&n.key));
+        let r = check(tree, n.right, Some(&n.key), hi);
+        assert!((l - r).abs() <= 1);
+        assert_eq!(1 + l.max(r), n.height);
+        n.height
+    }
+
+    #[test]
+    fn add_find_remove() {
+        let mut tree = ArenaAvl::new();
+        for i in 0..100 {
+            assert_eq!(None, tree.add(format!("{:03}", i), i));
+        }
+        check(&tree, tree.root, None, None);
+        assert_eq!(100, tree.len());
+        assert!(tree.height() <= 7);
+        assert_eq!(Some(&42), tree.find("042"));
+        assert_eq!(Some(42), tree.add(String::from("042"), 420));
+        assert!(tree.update("100", 0).is_err());
+        assert!(tree.update("007", 70).is_ok());
+        *tree.find_mut("008").unwrap() += 1;
+        assert_eq!(Some(9), tree.find_and_clone("008"));
+        assert_eq!(Some(&0), tree.min_val());
+        assert_eq!(Some(99), tree.max_val_clone());
+
+        for i in (0..100).filter(|i| i % 2 == 0) {
+            assert!(tree.remove(&format!("{:03}", i)).is_some());
+            check(&tree, tree.root, None, None);
+        }
+        assert_eq!(None, tree.remove("000"));
+        assert_eq!(50, tree.len());
+
+        // 删除留下的槽位被复用，不会继续增长
+        for i in 0..50 {
+            tree.add(format!("x{}", i), i);
+        }
+        assert_eq!(100, tree.slots());
+        check(&tree, tree.root, None, None);
+    }
+
+    #[test]
+    fn random_against_avl_tree() {
+        let mut arena = ArenaAvl::new();
+        let mut avl: AVLTree<u32> = AVLTree::new();
+        let model = check_against_model(&mut arena, 29, 3000, 500);
+        assert_eq!(model, check_against_model(&mut avl, 29, 3000, 500));
+        check(&arena, arena.root, None, None);
+        assert_eq!(model.len(), arena.len());
+        assert!(arena.slots() - arena.free.len() == arena.len());
+
+        let expect: Vec<(&String, &u32)> = model.iter().collect();
+        assert_eq!(expect, arena.iter().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,147 @@
+// 引用计数审计：检查 BinaryNode 树中每个节点恰好被父节点（或根）强引用一次、被每个孩子的 top 弱引用一次；
+// 多出来的计数说明外部还留着句柄，top 指错说明有子树脱离了原来的位置
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::rc::Rc;
+
+use super::binary::{BinaryNode, Summary};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Issue {
+    // 节点的强引用多于树内链接，外部仍持有该节点的 Rc
+    LeakedNode { key: String, extra: usize },
+    // 指向节点的 Weak 多于指回它的孩子，外部仍持有 Weak
+    LeakedWeak { key: String, extra: usize },
+    // 孩子的 top 没有指回父节点，top 为 None 时 found 为 None
+    BrokenTop { key: String, parent: String, found: Option<String> },
+    // 子树的根仍指向一个存活的节点，但那个节点并不以它为孩子
+    Orphan { key: String, top: String },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    pub nodes: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl AuditReport {
+    pub fn is_clean(&self) -> bool { self.issues.is_empty() }
+}
+
+type NodeRc<V, S> = Rc<RefCell<BinaryNode<V, S>>>;
+
+fn _top<V: Clone + Display, S: Summary<V>>(node: &NodeRc<V, S>) -> Option<NodeRc<V, S>> {
+    node.as_ref().borrow().top().as_ref().and_then(|w| w.upgrade())
+}
+
+fn _is_child<V: Clone + Display, S: Summary<V>>(parent: &NodeRc<V, S>, node: &NodeRc<V, S>) -> bool {
+    let p = parent.as_ref().borrow();
+    p.left().as_ref().is_some_and(|l| Rc::ptr_eq(l, node)) || p.right().as_ref().is_some_and(|r| Rc::ptr_eq(r, node))
+}
+
+// root 由调用方的一个位置持有（树的 root 字段或一个局部变量），该位置算作根的唯一强引用
+pub fn audit<V: Clone + Display, S: Summary<V>>(root: Option<&NodeRc<V, S>>) -> AuditReport {
+    let mut report = AuditReport::default();
+    let root = match root {
+        None => return report,
+        Some(r) => r,
+    };
+    if let Some(top) = _top(root) {
+        if !_is_child(&top, root) {
+            let key = root.as_ref().borrow().key().clone();
+            report.issues.push(Issue::Orphan { key, top: top.as_ref().borrow().key().clone() });
+        }
+    }
+
+    // 栈里每个节点只有一份克隆，检查时它的强引用应为 链接 + 这份克隆 = 2
+    let mut stack = vec![Rc::clone(root)];
+    while let Some(node) = stack.pop() {
+        report.nodes += 1;
+        let b = node.as_ref().borrow();
+        let key = b.key().clone();
+        let strong = Rc::strong_count(&node);
+        if strong > 2 {
+            report.issues.push(Issue::LeakedNode { key: key.clone(), extra: strong - 2 });
+        }
+
+        let mut linked_back = 0;
+        for child in b.left().iter().chain(b.right().iter()) {
+            match _top(child) {
+                Some(ref t) if Rc::ptr_eq(t, &node) => linked_back += 1,
+                found => report.issues.push(Issue::BrokenTop {
+                    key: child.as_ref().borrow().key().clone(),
+                    parent: key.clone(),
+                    found: found.map(|t| t.as_ref().borrow().key().clone()),
+                }),
+            }
+            stack.push(Rc::clone(child));
+        }
+        let weak = Rc::weak_count(&node);
+        if weak > linked_back {
+            report.issues.push(Issue::LeakedWeak { key, extra: weak - linked_back });
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tree::avl::AVLTree;
+    use tree::binary::{link_left, link_right};
+    use tree::search::SearchTree;
+
+    fn node(key: &str) -> NodeRc<i32, ()> { Rc::new(RefCell::new(BinaryNode::new(key.to_string(), 0))) }
+
+    #[test]
+    fn clean_tree_and_leaked_handles() {
+        let mut tree: AVLTree<i32> = AVLTree::new();
+        for i in 0..64 {
+            tree.add(format!("{:02}", i * 7 % 64), i);
+        }
+        for i in 0..20 {
+            tree.remove(&format!("{:02}", i * 3));
+        }
+        let report = tree.audit();
+        assert!(report.is_clean(), "{:?}", report.issues);
+        assert_eq!(64 - 20, report.nodes);
+
+        // 外部持有的节点和弱引用都会被报告，get 返回的守卫也持有节点
+        let held_node = tree.find_node(&String::from("10")).unwrap();
+        let held_value = tree.get(&String::from("11")).unwrap();
+        let held_weak = Rc::downgrade(&tree.find_node(&String::from("13")).unwrap());
+        let issues = tree.audit().issues;
+        assert_eq!(3, issues.len());
+        assert!(issues.contains(&Issue::LeakedNode { key: String::from("10"), extra: 1 }));
+        assert!(issues.contains(&Issue::LeakedNode { key: String::from("11"), extra: 1 }));
+        assert!(issues.contains(&Issue::LeakedWeak { key: String::from("13"), extra: 1 }));
+
+        drop((held_node, held_value, held_weak));
+        assert!(tree.audit().is_clean());
+    }
+
+    #[test]
+    fn broken_top_and_orphans() {
+        let (a, b, c, d) = (node("a"), node("b"), node("c"), node("d"));
+        link_left(Rc::clone(&b), Some(Rc::clone(&a)));
+        link_right(Rc::clone(&b), Some(Rc::clone(&c)));
+        drop((a, c));
+        assert!(audit(Some(&b)).is_clean());
+
+        // c 的 top 改指向 d，但 d 并不以 c 为孩子
+        let c = b.as_ref().borrow().right().clone().unwrap();
+        c.as_ref().borrow_mut().set_top(Some(Rc::downgrade(&d)));
+        let report = audit(Some(&b));
+        assert_eq!(3, report.nodes);
+        assert!(report.issues.contains(&Issue::BrokenTop {
+            key: String::from("c"),
+            parent: String::from("b"),
+            found: Some(String::from("d")),
+        }));
+
+        // 单独审计 c：它从 d 看是孤儿，同时 b 的 right 与局部变量 c 各持有一份强引用
+        let issues = audit(Some(&c)).issues;
+        assert!(issues.contains(&Issue::Orphan { key: String::from("c"), top: String::from("d") }));
+        assert!(issues.contains(&Issue::LeakedNode { key: String::from("c"), extra: 1 }));
+    }
+}
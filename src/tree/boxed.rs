@@ -0,0 +1,356 @@
+// 孩子用 Option<Box<..>> 独占的 AVL 树：没有父指针，插入删除都递归完成，取值直接得到 &V / &mut V
+use std::cmp::Ordering;
+use std::mem;
+
+use super::memory::{HeapSize, MemoryUsage};
+use super::search::Lookup;
+
+type Link<V> = Option<Box<BoxNode<V>>>;
+
+struct BoxNode<V> {
+    key: String,
+    value: V,
+    // 叶子为 0，与 BinaryNode::height 一致
+    height: i32,
+    left: Link<V>,
+    right: Link<V>,
+}
+
+pub struct BoxAvlTree<V> {
+    root: Link<V>,
+    len: usize,
+}
+
+fn _height<V>(link: &Link<V>) -> i32 {
+    link.as_ref().map_or(-1, |n| n.height)
+}
+
+impl<V> BoxNode<V> {
+    fn fix_height(&mut self) {
+        self.height = 1 + _height(&self.left).max(_height(&self.right));
+    }
+
+    fn balance_factor(&self) -> i32 { _height(&self.left) - _height(&self.right) }
+}
+
+fn _rotate_right<V>(mut node: Box<BoxNode<V>>) -> Box<BoxNode<V>> {
+    let mut l = node.left.take().unwrap();
+    node.left = l.right.take();
+    node.fix_height();
+    l.right = Some(node);
+    l.fix_height();
+    l
+}
+
+fn _rotate_left<V>(mut node: Box<BoxNode<V>>) -> Box<BoxNode<V>> {
+    let mut r = node.right.take().unwrap();
+    node.right = r.left.take();
+    node.fix_height();
+    r.left = Some(node);
+    r.fix_height();
+    r
+}
+
+// 更新高度并在失衡时旋转，返回新的子树根
+fn _rebalance<V>(mut node: Box<BoxNode<V>>) -> Box<BoxNode<V>> {
+    node.fix_height();
+    let bf = node.balance_factor();
+    if bf > 1 {
+        if node.left.as_ref().unwrap().balance_factor() < 0 {
+            node.left = node.left.take().map(_rotate_left);
+        }
+        _rotate_right(node)
+    } else if bf < -1 {
+        if node.right.as_ref().unwrap().balance_factor() > 0 {
+            node.right = node.right.take().map(_rotate_right);
+        }
+        _rotate_left(node)
+    } else {
+        node
+    }
+}
+
+fn _insert<V>(link: Link<V>, key: String, value: V) -> (Box<BoxNode<V>>, Option<V>) {
+    let mut node = match link {
+        None => return (Box::new(BoxNode { key, value, height: 0, left: None, right: None }), None),
+        Some(n) => n,
+    };
+    let old = match key.cmp(&node.key) {
+        Ordering::Equal => {
+            let old = mem::replace(&mut node.value, value);
+            return (node, Some(old));
+        }
+        Ordering::Less => {
+            let (l, old) = _insert(node.left.take(), key, value);
+            node.left = Some(l);
+            old
+        }
+        Ordering::Greater => {
+            let (r, old) = _insert(node.right.take(), key, value);
+            node.right = Some(r);
+            old
+        }
+    };
+    // 替换旧值时形状不变
+    if old.is_some() {
+        return (node, old);
+    }
+    (_rebalance(node), None)
+}
+
+// 摘下子树中最小的节点，返回剩余的子树和最小节点
+fn _remove_min<V>(mut node: Box<BoxNode<V>>) -> (Link<V>, Box<BoxNode<V>>) {
+    match node.left.take() {
+        None => (node.right.take(), node),
+        Some(l) => {
+            let (l, min) = _remove_min(l);
+            node.left = l;
+            (Some(_rebalance(node)), min)
+        }
+    }
+}
+
+fn _remove<V>(link: Link<V>, key: &str) -> (Link<V>, Option<V>) {
+    let mut node = match link {
+        None => return (None, None),
+        Some(n) => n,
+    };
+    let removed = match key.cmp(&node.key) {
+        Ordering::Less => {
+            let (l, removed) = _remove(node.left.take(), key);
+            node.left = l;
+            removed
+        }
+        Ordering::Greater => {
+            let (r, removed) = _remove(node.right.take(), key);
+            node.right = r;
+            removed
+        }
+        Ordering::Equal => {
+            let node = *node;
+            let rest = match (node.left, node.right) {
+                (None, r) => r,
+                (l, None) => l,
+                // 两个孩子时用后继节点顶替
+                (l, Some(r)) => {
+                    let (r, mut succ) = _remove_min(r);
+                    succ.left = l;
+                    succ.right = r;
+                    Some(_rebalance(succ))
+                }
+            };
+            return (rest, Some(node.value));
+        }
+    };
+    if removed.is_none() {
+        return (Some(node), None);
+    }
+    (Some(_rebalance(node)), removed)
+}
+
+impl<V> Default for BoxAvlTree<V> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<V> BoxAvlTree<V> {
+    pub fn new() -> Self { Self { root: None, len: 0 } }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    pub fn height(&self) -> i32 { _height(&self.root) }
+
+    pub fn find(&self, key: &str) -> Option<&V> {
+        let mut cur = self.root.as_ref();
+        while let Some(n) = cur {
+            cur = match key.cmp(&n.key) {
+                Ordering::Less => n.left.as_ref(),
+                Ordering::Greater => n.right.as_ref(),
+                Ordering::Equal => return Some(&n.value),
+            };
+        }
+        None
+    }
+
+    pub fn find_mut(&mut self, key: &str) -> Option<&mut V> {
+        let mut cur = self.root.as_mut();
+        while let Some(n) = cur {
+            cur = match key.cmp(&n.key) {
+                Ordering::Less => n.left.as_mut(),
+                Ordering::Greater => n.right.as_mut(),
+                Ordering::Equal => return Some(&mut n.value),
+            };
+        }
+        None
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool { self.find(key).is_some() }
+
+    // 插入，key 已存在时替换并返回旧值
+    pub fn add(&mut self, key: String, value: V) -> Option<V> {
+        let (root, old) = _insert(self.root.take(), key, value);
+        self.root = Some(root);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn update(&mut self, key: &str, value: V) -> Result<(), String> {
+        match self.find_mut(key) {
+            None => Err(format!("node={} not exists", key)),
+            Some(v) => {
+                *v = value;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let (root, removed) = _remove(self.root.take(), key);
+        self.root = root;
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    pub fn min_val(&self) -> Option<&V> {
+        let mut n = self.root.as_ref()?;
+        while let Some(ref l) = n.left {
+            n = l;
+        }
+        Some(&n.value)
+    }
+
+    pub fn max_val(&self) -> Option<&V> {
+        let mut n = self.root.as_ref()?;
+        while let Some(ref r) = n.right {
+            n = r;
+        }
+        Some(&n.value)
+    }
+
+    // 中序遍历
+    pub fn iter(&self) -> Iter<'_, V> {
+        let mut it = Iter { stack: Vec::new() };
+        it.push_left(self.root.as_deref());
+        it
+    }
+}
+
+impl<V: Clone> BoxAvlTree<V> {
+    pub fn find_and_clone(&self, key: &str) -> Option<V> { self.find(key).cloned() }
+
+    pub fn min_val_clone(&self) -> Option<V> { self.min_val().cloned() }
+
+    pub fn max_val_clone(&self) -> Option<V> { self.max_val().cloned() }
+}
+
+#[allow(clippy::ptr_arg)]
+impl<V: Clone> Lookup<V> for BoxAvlTree<V> {
+    fn add(&mut self, key: String, value: V) -> Option<V> { BoxAvlTree::add(self, key, value) }
+
+    fn remove(&mut self, key: &String) -> Option<V> { BoxAvlTree::remove(self, key) }
+
+    fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &String, f: F) -> Option<R> { self.find(key).map(f) }
+
+    fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, key: &String, f: F) -> Option<R> {
+        self.find_mut(key).map(f)
+    }
+}
+
+impl<V: HeapSize> BoxAvlTree<V> {
+    // 每个节点一次 Box 分配，值内嵌在节点中
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage {
+            nodes: self.len,
+            node_bytes: self.len * mem::size_of::<BoxNode<V>>(),
+            ..MemoryUsage::default()
+        };
+        for (k, v) in self.iter() {
+            usage.key_bytes += k.capacity();
+            usage.value_bytes += v.heap_size();
+        }
+        usage
+    }
+}
+
+pub struct Iter<'a, V: 'a> {
+    stack: Vec<&'a BoxNode<V>>,
+}
+
+impl<'a, V> Iter<'a, V> {
+    fn push_left(&mut self, mut cur: Option<&'a BoxNode<V>>) {
+        while let Some(n) = cur {
+            self.stack.push(n);
+            cur = n.left.as_deref();
+        }
+    }
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (&'a String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.stack.pop()?;
+        self.push_left(n.right.as_deref());
+        Some((&n.key, &n.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::check_against_model;
+
+    // 校验有序、平衡与高度缓存，返回子树高度
+    fn check<V>(link: &Link<V>, lo: Option<&str>, hi: Option<&str>) -> i32 {
+        let n = match *link {
+            None => return -1,
+            Some(ref n) => n,
+        };
+        assert!(lo.is_none_or(|lo| lo < n.key.as_str()));
+        assert!(hi.is_none_or(|hi| n.key.as_str() < hi));
+        let l = check(&n.left, lo, Some(&n.key));
+        let r = check(&n.right, Some(&n.key), hi);
+        assert!((l - r).abs() <= 1);
+        assert_eq!(1 + l.max(r), n.height);
+        n.height
+    }
+
+    #[test]
+    fn borrow_without_guards() {
+        let mut tree = BoxAvlTree::new();
+        for i in 0..31 {
+            tree.add(format!("{:02}", i), vec![i]);
+        }
+        check(&tree.root, None, None);
+        assert_eq!(4, tree.height());
+
+        // 直接拿到 &mut V，无需 RefCell
+        tree.find_mut("07").unwrap().push(70);
+        let v: &Vec<i32> = tree.find("07").unwrap();
+        assert_eq!(&vec![7, 70], v);
+        assert_eq!(Some(vec![8]), tree.add(String::from("08"), vec![80]));
+        assert!(tree.update("99", vec![]).is_err());
+        assert_eq!(Some(&vec![0]), tree.min_val());
+        assert_eq!(Some(vec![30]), tree.max_val_clone());
+
+        assert_eq!(Some(vec![15]), tree.remove("15"));
+        assert_eq!(None, tree.remove("15"));
+        check(&tree.root, None, None);
+        assert_eq!(30, tree.len());
+    }
+
+    #[test]
+    fn random_against_btreemap() {
+        let mut tree = BoxAvlTree::new();
+        let model = check_against_model(&mut tree, 37, 4000, 700);
+        check(&tree.root, None, None);
+        assert_eq!(model.len(), tree.len());
+        let expect: Vec<(&String, &u32)> = model.iter().collect();
+        assert_eq!(expect, tree.iter().collect::<Vec<_>>());
+    }
+}
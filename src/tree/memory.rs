@@ -0,0 +1,141 @@
+// 内存占用统计：按节点结构、引用计数与借用标记、键、值分别累计字节数，便于比较不同的树实现
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::mem::size_of;
+use std::rc::Rc;
+
+use super::binary::{BinaryNode, Summary};
+
+// 值自身在堆上额外持有的字节数，不含 size_of::<Self>()
+pub trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+macro_rules! no_heap {
+    ($($t:ty),*) => {
+        $(impl HeapSize for $t {
+            fn heap_size(&self) -> usize { 0 }
+        })*
+    };
+}
+
+no_heap!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char, ());
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize { self.capacity() }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize { self.as_ref().map_or(0, HeapSize::heap_size) }
+}
+
+impl<T: HeapSize> HeapSize for Box<T> {
+    fn heap_size(&self) -> usize { size_of::<T>() + (**self).heap_size() }
+}
+
+impl<A: HeapSize, B: HeapSize> HeapSize for (A, B) {
+    fn heap_size(&self) -> usize { self.0.heap_size() + self.1.heap_size() }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub nodes: usize,
+    // 节点结构体本身（包括空闲槽位）
+    pub node_bytes: usize,
+    // Rc 的强弱计数与 RefCell 的借用标记
+    pub rc_overhead: usize,
+    // 键字符串的容量
+    pub key_bytes: usize,
+    // 值单独分配时为 size_of::<V>() 加上值的堆内存；值内嵌在节点中时只计堆内存
+    pub value_bytes: usize,
+}
+
+impl MemoryUsage {
+    pub fn total(&self) -> usize { self.node_bytes + self.rc_overhead + self.key_bytes + self.value_bytes }
+
+    // 平均每个元素的字节数
+    pub fn per_node(&self) -> f64 {
+        if self.nodes == 0 { 0.0 } else { self.total() as f64 / self.nodes as f64 }
+    }
+}
+
+// 一个 Rc<RefCell<T>> 分配在 T 之外的开销
+fn _rc_cell_overhead<T>() -> usize {
+    2 * size_of::<usize>() + size_of::<RefCell<T>>() - size_of::<T>()
+}
+
+// 统计以 root 为根的 BinaryNode 树，值内嵌在节点中，每个节点一次 Rc<RefCell<..>> 分配
+pub fn binary_usage<V, S>(root: &Option<Rc<RefCell<BinaryNode<V, S>>>>) -> MemoryUsage
+where
+    V: Clone + Display + HeapSize,
+    S: Summary<V>,
+{
+    let mut usage = MemoryUsage::default();
+    let mut stack: Vec<Rc<RefCell<BinaryNode<V, S>>>> = root.iter().cloned().collect();
+    while let Some(rc) = stack.pop() {
+        let node = rc.as_ref().borrow();
+        usage.nodes += 1;
+        usage.key_bytes += node.key().capacity();
+        usage.value_bytes += node.value().heap_size();
+        stack.extend(node.left().iter().cloned());
+        stack.extend(node.right().iter().cloned());
+    }
+    usage.node_bytes = usage.nodes * size_of::<BinaryNode<V, S>>();
+    usage.rc_overhead = usage.nodes * _rc_cell_overhead::<BinaryNode<V, S>>();
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tree::arena::ArenaAvl;
+    use tree::avl::AVLTree;
+    use tree::boxed::BoxAvlTree;
+    use tree::search::SearchTree;
+
+    #[test]
+    fn heap_size_of_values() {
+        let s = String::with_capacity(10);
+        assert_eq!(10, s.heap_size());
+        assert_eq!(0, 7u64.heap_size());
+        let v = vec![String::with_capacity(3), String::with_capacity(5)];
+        assert_eq!(v.capacity() * size_of::<String>() + 8, v.heap_size());
+        assert_eq!(size_of::<u32>(), Box::new(1u32).heap_size());
+        assert_eq!(0, None::<String>.heap_size());
+    }
+
+    #[test]
+    fn compare_representations() {
+        let mut avl: AVLTree<u64> = AVLTree::new();
+        let mut boxed = BoxAvlTree::new();
+        let mut arena = ArenaAvl::new();
+        assert_eq!(MemoryUsage::default(), avl.memory_usage());
+        for i in 0..100u64 {
+            let key = format!("{:04}", i);
+            avl.add(key.clone(), i);
+            boxed.add(key.clone(), i);
+            arena.add(key, i);
+        }
+
+        let a = avl.memory_usage();
+        assert_eq!(100, a.nodes);
+        assert_eq!(100 * 4, a.key_bytes);
+        assert_eq!(0, a.value_bytes);
+        assert_eq!(100 * (2 * size_of::<usize>() + size_of::<isize>()), a.rc_overhead);
+        assert_eq!(a.total(), a.node_bytes + a.rc_overhead + 400);
+
+        // 去掉 Rc/RefCell 后，每个元素占用更少
+        let (b, r) = (boxed.memory_usage(), arena.memory_usage());
+        assert_eq!((100, 100), (b.nodes, r.nodes));
+        assert_eq!((0, 0), (b.rc_overhead, r.rc_overhead));
+        assert!(b.total() < a.total());
+        assert!(r.node_bytes >= 100 * size_of::<u64>());
+        assert!(a.per_node() > b.per_node());
+    }
+}
@@ -0,0 +1,216 @@
+// 多版本并发控制：当前版本是一棵 PersistentAvl，写入生成新版本后整体替换发布
+// 每个版本是一个 Arc<Snapshot>，读者只在克隆这个 Arc 的一瞬间加读锁，写者只在替换它的一瞬间加写锁；
+// 新树在锁外计算，版本的最后一个持有者释放时它独有的节点随之回收，不需要额外的回收列表
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use super::persistent::{Iter, PersistentAvl};
+
+// 某一时刻的只读视图，可以跨线程传递和长期持有
+pub struct Snapshot<V> {
+    version: u64,
+    tree: PersistentAvl<V>,
+}
+
+impl<V> Clone for Snapshot<V> {
+    fn clone(&self) -> Self { Self { version: self.version, tree: self.tree.clone() } }
+}
+
+impl<V> Snapshot<V> {
+    pub fn version(&self) -> u64 { self.version }
+
+    pub fn len(&self) -> usize { self.tree.len() }
+
+    pub fn is_empty(&self) -> bool { self.tree.is_empty() }
+
+    pub fn get(&self, key: &str) -> Option<&V> { self.tree.find(key) }
+
+    pub fn contains_key(&self, key: &str) -> bool { self.tree.contains_key(key) }
+
+    pub fn iter(&self) -> Iter<'_, V> { self.tree.iter() }
+
+    pub fn tree(&self) -> &PersistentAvl<V> { &self.tree }
+}
+
+pub struct MvccMap<V> {
+    // 锁只保护 Arc 的克隆与替换，都是 O(1)
+    current: RwLock<Arc<Snapshot<V>>>,
+    // 写者之间互斥，保证新版本总是基于最新版本生成
+    writer: Mutex<()>,
+}
+
+impl<V> Default for MvccMap<V> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<V> MvccMap<V> {
+    pub fn new() -> Self {
+        Self { current: RwLock::new(Arc::new(Snapshot { version: 0, tree: PersistentAvl::new() })), writer: Mutex::new(()) }
+    }
+
+    pub fn snapshot(&self) -> Snapshot<V> { Snapshot::clone(&self._current()) }
+
+    pub fn version(&self) -> u64 { self._current().version }
+
+    // 基于最新版本计算新树并发布，返回新版本号；读者不会等待写者计算新树
+    // f 返回 None 表示放弃本次写入，版本号不变
+    pub fn write<F>(&self, f: F) -> Option<u64>
+    where
+        F: FnOnce(&PersistentAvl<V>) -> Option<PersistentAvl<V>>,
+    {
+        // 锁里没有数据，f 在别的写入中 panic 留下的中毒标记可以忽略
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let base = self._current();
+        let tree = f(&base.tree)?;
+        let version = base.version + 1;
+        let next = Arc::new(Snapshot { version, tree });
+        // 旧版本在锁外释放，仍被快照引用时由最后一个快照回收
+        let _old = ::std::mem::replace(&mut *self.current.write().unwrap_or_else(PoisonError::into_inner), next);
+        Some(version)
+    }
+
+    fn _current(&self) -> Arc<Snapshot<V>> { Arc::clone(&self.current.read().unwrap_or_else(PoisonError::into_inner)) }
+}
+
+impl<V: Clone> MvccMap<V> {
+    pub fn insert(&self, key: String, value: V) -> u64 {
+        self.write(|t| Some(t.insert(key, value))).unwrap()
+    }
+
+    // key 不存在时不产生新版本
+    pub fn remove(&self, key: &str) -> Option<u64> {
+        self.write(|t| if t.contains_key(key) { Some(t.remove(key)) } else { None })
+    }
+
+    pub fn update(&self, key: &str, value: V) -> Result<u64, String> {
+        let mut err = None;
+        let version = self.write(|t| match t.update(key, value) {
+            Ok(t) => Some(t),
+            Err(e) => {
+                err = Some(e);
+                None
+            }
+        });
+        match err {
+            Some(e) => Err(e),
+            None => Ok(version.unwrap()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic;
+    use std::thread;
+    use testing::Lcg;
+
+    #[test]
+    fn snapshots_are_isolated_and_reclaimed() {
+        let map = MvccMap::new();
+        let tracked = Arc::new(String::from("v1"));
+        let weak = Arc::downgrade(&tracked);
+        assert_eq!(1, map.insert(String::from("a"), tracked));
+        let s1 = map.snapshot();
+
+        assert_eq!(2, map.insert(String::from("a"), Arc::new(String::from("v2"))));
+        assert_eq!(3, map.insert(String::from("b"), Arc::new(String::from("b"))));
+        assert_eq!(None, map.remove("missing"));
+        assert!(map.update("missing", Arc::new(String::new())).is_err());
+        assert_eq!(3, map.version());
+
+        // 旧快照不受后续写入影响
+        assert_eq!((1, 1), (s1.version(), s1.len()));
+        assert_eq!("v1", s1.get("a").unwrap().as_str());
+        assert_eq!("v2", map.snapshot().get("a").unwrap().as_str());
+
+        // 最后一个持有旧版本的快照释放后，旧值被回收
+        let s1_copy = s1.clone();
+        drop(s1);
+        assert!(weak.upgrade().is_some());
+        drop(s1_copy);
+        assert!(weak.upgrade().is_none());
+    }
+
+    // 长期持有旧快照不会让中间版本滞留：每个版本只由引用它的快照保活
+    #[test]
+    fn replaced_versions_are_freed_while_snapshot_held() {
+        let map = MvccMap::new();
+        let tracked = Arc::new(0);
+        map.insert(String::from("a"), Arc::clone(&tracked));
+        let held = map.snapshot();
+
+        let mut weak = Vec::new();
+        for i in 1..1000 {
+            let v = Arc::new(i);
+            weak.push(Arc::downgrade(&v));
+            map.insert(String::from("a"), v);
+        }
+        // 只剩当前版本里的值
+        assert_eq!(1, weak.iter().filter(|w| w.upgrade().is_some()).count());
+        assert_eq!(2, Arc::strong_count(&tracked));
+        drop(held);
+        assert_eq!(1, Arc::strong_count(&tracked));
+        assert_eq!(1000, map.version());
+    }
+
+    #[test]
+    fn panicking_writer_does_not_block_later_writes() {
+        let map = MvccMap::new();
+        map.insert(String::from("a"), 1);
+        let r = panic::catch_unwind(panic::AssertUnwindSafe(|| map.write(|_| panic!("abort this write"))));
+        assert!(r.is_err());
+        assert_eq!(1, map.version());
+        assert_eq!(2, map.insert(String::from("b"), 2));
+        assert_eq!(Some(&2), map.snapshot().get("b"));
+    }
+
+    // 写者在账户间转账并整批发布，读者在任何快照上看到的总额都不变
+    #[test]
+    fn readers_see_consistent_versions() {
+        const ACCOUNTS: u32 = 50;
+        let map = Arc::new(MvccMap::new());
+        map.write(|t| Some((0..ACCOUNTS).fold(t.clone(), |t, i| t.insert(format!("acct{:02}", i), 100i64))));
+
+        let writer = {
+            let map = Arc::clone(&map);
+            thread::spawn(move || {
+                let mut rng = Lcg::new(3);
+                for _ in 0..2000 {
+                    let seed = rng.next_u32();
+                    let (from, to) = (format!("acct{:02}", (seed >> 16) % ACCOUNTS), format!("acct{:02}", (seed >> 8) % ACCOUNTS));
+                    let amount = (seed % 30) as i64;
+                    map.write(|t| {
+                        let t = t.insert(from.clone(), t.find(&from).unwrap() - amount);
+                        Some(t.insert(to.clone(), t.find(&to).unwrap() + amount))
+                    });
+                }
+            })
+        };
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let map = Arc::clone(&map);
+                thread::spawn(move || {
+                    let held = map.snapshot();
+                    let mut last = 0;
+                    for _ in 0..500 {
+                        let s = map.snapshot();
+                        assert!(s.version() >= last);
+                        last = s.version();
+                        assert_eq!(ACCOUNTS as usize, s.len());
+                        assert_eq!(ACCOUNTS as i64 * 100, s.iter().map(|(_, v)| *v).sum::<i64>());
+                    }
+                    // 长期持有的快照始终不变
+                    assert_eq!(ACCOUNTS as i64 * 100, held.iter().map(|(_, v)| *v).sum::<i64>());
+                    held.version()
+                })
+            })
+            .collect();
+
+        writer.join().unwrap();
+        for r in readers {
+            assert!(r.join().unwrap() <= 2001);
+        }
+        assert_eq!(2001, map.version());
+    }
+}
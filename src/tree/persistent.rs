@@ -0,0 +1,298 @@
+// 持久化 AVL：insert / remove 返回新的树，只复制从根到修改点的路径，其余子树与旧版本共享
+// 节点用 Arc 共享，因此各个版本可以放心地跨线程持有
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+type Tree<V> = Option<Arc<PNode<V>>>;
+
+struct PNode<V> {
+    key: String,
+    value: V,
+    // 叶子为 0，与 BinaryNode::height 一致
+    height: i32,
+    left: Tree<V>,
+    right: Tree<V>,
+}
+
+pub struct PersistentAvl<V> {
+    root: Tree<V>,
+    len: usize,
+}
+
+fn _height<V>(t: &Tree<V>) -> i32 {
+    t.as_ref().map_or(-1, |n| n.height)
+}
+
+fn _make<V>(key: String, value: V, left: Tree<V>, right: Tree<V>) -> Arc<PNode<V>> {
+    let height = 1 + _height(&left).max(_height(&right));
+    Arc::new(PNode { key, value, height, left, right })
+}
+
+// 以 (key, value) 为根、left/right 为孩子建新节点，失衡时顺带旋转；只新建路径上的节点
+fn _balance<V: Clone>(key: String, value: V, left: Tree<V>, right: Tree<V>) -> Arc<PNode<V>> {
+    let (hl, hr) = (_height(&left), _height(&right));
+    if hl > hr + 1 {
+        let l = left.unwrap();
+        if _height(&l.left) >= _height(&l.right) {
+            let r = _make(key, value, l.right.clone(), right);
+            _make(l.key.clone(), l.value.clone(), l.left.clone(), Some(r))
+        } else {
+            let lr = l.right.as_ref().unwrap();
+            let a = _make(l.key.clone(), l.value.clone(), l.left.clone(), lr.left.clone());
+            let b = _make(key, value, lr.right.clone(), right);
+            _make(lr.key.clone(), lr.value.clone(), Some(a), Some(b))
+        }
+    } else if hr > hl + 1 {
+        let r = right.unwrap();
+        if _height(&r.right) >= _height(&r.left) {
+            let l = _make(key, value, left, r.left.clone());
+            _make(r.key.clone(), r.value.clone(), Some(l), r.right.clone())
+        } else {
+            let rl = r.left.as_ref().unwrap();
+            let a = _make(key, value, left, rl.left.clone());
+            let b = _make(r.key.clone(), r.value.clone(), rl.right.clone(), r.right.clone());
+            _make(rl.key.clone(), rl.value.clone(), Some(a), Some(b))
+        }
+    } else {
+        _make(key, value, left, right)
+    }
+}
+
+// 返回新的子树根，以及 key 是否原本就存在
+fn _insert<V: Clone>(t: &Tree<V>, key: String, value: V) -> (Arc<PNode<V>>, bool) {
+    let n = match *t {
+        None => return (_make(key, value, None, None), false),
+        Some(ref n) => n,
+    };
+    match key.cmp(&n.key) {
+        Ordering::Equal => (_make(key, value, n.left.clone(), n.right.clone()), true),
+        Ordering::Less => {
+            let (l, existed) = _insert(&n.left, key, value);
+            (_balance(n.key.clone(), n.value.clone(), Some(l), n.right.clone()), existed)
+        }
+        Ordering::Greater => {
+            let (r, existed) = _insert(&n.right, key, value);
+            (_balance(n.key.clone(), n.value.clone(), n.left.clone(), Some(r)), existed)
+        }
+    }
+}
+
+// 摘下最小节点，返回剩余子树和最小节点的键值
+fn _remove_min<V: Clone>(n: &Arc<PNode<V>>) -> (Tree<V>, String, V) {
+    match n.left {
+        None => (n.right.clone(), n.key.clone(), n.value.clone()),
+        Some(ref l) => {
+            let (l, k, v) = _remove_min(l);
+            (Some(_balance(n.key.clone(), n.value.clone(), l, n.right.clone())), k, v)
+        }
+    }
+}
+
+// key 不存在时返回 None，调用方直接共享原树
+fn _remove<V: Clone>(t: &Tree<V>, key: &str) -> Option<Tree<V>> {
+    let n = t.as_ref()?;
+    match key.cmp(&n.key) {
+        Ordering::Less => {
+            let l = _remove(&n.left, key)?;
+            Some(Some(_balance(n.key.clone(), n.value.clone(), l, n.right.clone())))
+        }
+        Ordering::Greater => {
+            let r = _remove(&n.right, key)?;
+            Some(Some(_balance(n.key.clone(), n.value.clone(), n.left.clone(), r)))
+        }
+        Ordering::Equal => match (&n.left, &n.right) {
+            (None, r) => Some(r.clone()),
+            (l, None) => Some(l.clone()),
+            (l, Some(r)) => {
+                let (r, k, v) = _remove_min(r);
+                Some(Some(_balance(k, v, l.clone(), r)))
+            }
+        },
+    }
+}
+
+// 克隆只增加根的引用计数，O(1)
+impl<V> Clone for PersistentAvl<V> {
+    fn clone(&self) -> Self { Self { root: self.root.clone(), len: self.len } }
+}
+
+impl<V> Default for PersistentAvl<V> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<V> PersistentAvl<V> {
+    pub fn new() -> Self { Self { root: None, len: 0 } }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    pub fn height(&self) -> i32 { _height(&self.root) }
+
+    pub fn find(&self, key: &str) -> Option<&V> {
+        let mut cur = self.root.as_ref();
+        while let Some(n) = cur {
+            cur = match key.cmp(&n.key) {
+                Ordering::Less => n.left.as_ref(),
+                Ordering::Greater => n.right.as_ref(),
+                Ordering::Equal => return Some(&n.value),
+            };
+        }
+        None
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool { self.find(key).is_some() }
+
+    // 两个版本是否为同一棵树（根节点相同）
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (None, None) => true,
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    // 中序遍历
+    pub fn iter(&self) -> Iter<'_, V> {
+        let mut it = Iter { stack: Vec::new() };
+        it.push_left(self.root.as_deref());
+        it
+    }
+}
+
+impl<V: Clone> PersistentAvl<V> {
+    // 返回插入后的新版本，key 已存在时替换
+    pub fn insert(&self, key: String, value: V) -> Self {
+        let (root, existed) = _insert(&self.root, key, value);
+        let len = if existed { self.len } else { self.len + 1 };
+        Self { root: Some(root), len }
+    }
+
+    // 返回删除后的新版本；key 不存在时与原版本共享同一棵树
+    pub fn remove(&self, key: &str) -> Self {
+        match _remove(&self.root, key) {
+            None => self.clone(),
+            Some(root) => Self { root, len: self.len - 1 },
+        }
+    }
+
+    pub fn update(&self, key: &str, value: V) -> Result<Self, String> {
+        if !self.contains_key(key) {
+            return Err(format!("node={} not exists", key));
+        }
+        Ok(self.insert(key.to_string(), value))
+    }
+
+    pub fn find_and_clone(&self, key: &str) -> Option<V> { self.find(key).cloned() }
+}
+
+pub struct Iter<'a, V: 'a> {
+    stack: Vec<&'a PNode<V>>,
+}
+
+impl<'a, V> Iter<'a, V> {
+    fn push_left(&mut self, mut cur: Option<&'a PNode<V>>) {
+        while let Some(n) = cur {
+            self.stack.push(n);
+            cur = n.left.as_deref();
+        }
+    }
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (&'a String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.stack.pop()?;
+        self.push_left(n.right.as_deref());
+        Some((&n.key, &n.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, HashSet};
+    use testing::Lcg;
+
+    fn check<V>(t: &Tree<V>) -> i32 {
+        match *t {
+            None => -1,
+            Some(ref n) => {
+                let (l, r) = (check(&n.left), check(&n.right));
+                assert!((l - r).abs() <= 1);
+                assert_eq!(1 + l.max(r), n.height);
+                n.height
+            }
+        }
+    }
+
+    fn nodes<V>(t: &Tree<V>, out: &mut HashSet<*const PNode<V>>) {
+        if let Some(ref n) = *t {
+            out.insert(&**n as *const PNode<V>);
+            nodes(&n.left, out);
+            nodes(&n.right, out);
+        }
+    }
+
+    // new 中不与 old 共享的节点个数
+    fn fresh<V>(old: &PersistentAvl<V>, new: &PersistentAvl<V>) -> usize {
+        let (mut a, mut b) = (HashSet::new(), HashSet::new());
+        nodes(&old.root, &mut a);
+        nodes(&new.root, &mut b);
+        b.difference(&a).count()
+    }
+
+    #[test]
+    fn versions_coexist() {
+        let v0 = PersistentAvl::new();
+        let v1 = v0.insert(String::from("timeout"), 30);
+        let v2 = v1.insert(String::from("retries"), 3);
+        let v3 = v2.insert(String::from("timeout"), 60);
+        let v4 = v3.remove("retries");
+        let v5 = v4.remove("missing");
+
+        assert!(v0.is_empty());
+        assert_eq!(Some(&30), v1.find("timeout"));
+        assert_eq!(None, v1.find("retries"));
+        assert_eq!(Some(&30), v2.find("timeout"));
+        assert_eq!(Some(3), v3.find_and_clone("retries"));
+        assert_eq!(Some(&60), v3.find("timeout"));
+        assert_eq!((1, 2, 2, 1), (v1.len(), v2.len(), v3.len(), v4.len()));
+        assert!(v5.ptr_eq(&v4));
+        assert!(v4.update("retries", 5).is_err());
+        assert_eq!(Some(&90), v4.update("timeout", 90).unwrap().find("timeout"));
+        assert_eq!(Some(&60), v4.find("timeout"));
+    }
+
+    #[test]
+    fn path_copying_shares_subtrees() {
+        let mut versions = vec![PersistentAvl::new()];
+        let mut models = vec![BTreeMap::new()];
+        let mut rng = Lcg::new(41);
+        for _ in 0..1500 {
+            let seed = rng.next_u32();
+            let key = format!("{}", (seed >> 16) % 600);
+            let (prev, mut model) = (versions.last().unwrap().clone(), models.last().unwrap().clone());
+            let next = if (seed >> 8) & 3 == 0 {
+                model.remove(&key);
+                prev.remove(&key)
+            } else {
+                model.insert(key.clone(), seed);
+                prev.insert(key, seed)
+            };
+            // 每次更新新建的节点数为 O(log n)
+            assert!(fresh(&prev, &next) <= 2 * (prev.height().max(next.height()) as usize + 2));
+            versions.push(next);
+            models.push(model);
+        }
+
+        // 所有历史版本仍然完好
+        for (v, m) in versions.iter().zip(models.iter()).step_by(100) {
+            check(&v.root);
+            assert_eq!(m.len(), v.len());
+            let expect: Vec<(&String, &u32)> = m.iter().collect();
+            assert_eq!(expect, v.iter().collect::<Vec<_>>());
+        }
+    }
+}
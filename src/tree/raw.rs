@@ -0,0 +1,606 @@
+// 用 NonNull 指针连接父子节点的 AVL 树，接口与 avl::AVLTree 一致
+// 所有解引用都集中在 ptr_core 模块的 unsafe fn 中，调用处用 SAFETY 注释说明为何节点仍然存活
+// 与 AVLTree 相比不提供 root/find_node：交出 Ptr 会让指针逃出树的所有权约定
+use std::cmp::Ordering;
+use std::mem;
+use std::ops::{Bound, Deref, DerefMut, RangeBounds};
+
+use tree::binary::Summary;
+use tree::search::Lookup;
+
+use self::ptr_core::{Ptr, RawNode};
+
+mod ptr_core {
+    use std::cmp::Ordering;
+    use std::marker::PhantomData;
+    use std::ptr::NonNull;
+
+    use tree::binary::Summary;
+
+    pub struct RawNode<V, S: Summary<V>> {
+        pub key: String,
+        pub value: V,
+        pub height: i32,
+        pub summary: S::Item,
+        pub parent: Option<Ptr<V, S>>,
+        pub left: Option<Ptr<V, S>>,
+        pub right: Option<Ptr<V, S>>,
+    }
+
+    // 指向堆上节点的指针，只能由 alloc 创建；可以复制，因此是否存活由调用方保证：
+    // 树独占自己的所有节点，free 之后该指针及其所有副本都不再使用
+    pub struct Ptr<V, S: Summary<V>>(NonNull<RawNode<V, S>>, PhantomData<RawNode<V, S>>);
+
+    impl<V, S: Summary<V>> Clone for Ptr<V, S> {
+        fn clone(&self) -> Self { *self }
+    }
+
+    impl<V, S: Summary<V>> Copy for Ptr<V, S> {}
+
+    impl<V, S: Summary<V>> PartialEq for Ptr<V, S> {
+        fn eq(&self, other: &Self) -> bool { self.0 == other.0 }
+    }
+
+    impl<V, S: Summary<V>> Ptr<V, S> {
+        pub fn alloc(key: String, value: V) -> Self {
+            let summary = S::measure(&value);
+            let node = Box::new(RawNode { key, value, height: 0, summary, parent: None, left: None, right: None });
+            Ptr(NonNull::from(Box::leak(node)), PhantomData)
+        }
+
+        /// # Safety
+        /// self 来自 alloc 且尚未释放；之后 self 及其所有副本都不能再使用
+        pub unsafe fn free(self) -> RawNode<V, S> {
+            // SAFETY: 由调用方保证指针来自 alloc 中泄漏的 Box 且只释放一次
+            unsafe { *Box::from_raw(self.0.as_ptr()) }
+        }
+
+        fn raw(self) -> *mut RawNode<V, S> { self.0.as_ptr() }
+
+        // 以下读写都只在表达式内部解引用，不会产生长期存活的引用
+
+        /// # Safety
+        /// self 指向的节点尚未释放，且调用期间没有其它对该节点的引用
+        pub unsafe fn left(self) -> Option<Ptr<V, S>> { unsafe { (*self.raw()).left } }
+
+        /// # Safety
+        /// 同 left
+        pub unsafe fn right(self) -> Option<Ptr<V, S>> { unsafe { (*self.raw()).right } }
+
+        /// # Safety
+        /// 同 left
+        pub unsafe fn parent(self) -> Option<Ptr<V, S>> { unsafe { (*self.raw()).parent } }
+
+        /// # Safety
+        /// 同 left
+        pub unsafe fn height(self) -> i32 { unsafe { (*self.raw()).height } }
+
+        /// # Safety
+        /// 同 left
+        pub unsafe fn summary(self) -> S::Item { unsafe { (*self.raw()).summary.clone() } }
+
+        /// # Safety
+        /// 同 left
+        pub unsafe fn measure(self) -> S::Item { unsafe { S::measure(&(*self.raw()).value) } }
+
+        /// # Safety
+        /// 同 left
+        pub unsafe fn set_left(self, p: Option<Ptr<V, S>>) { unsafe { (*self.raw()).left = p } }
+
+        /// # Safety
+        /// 同 left
+        pub unsafe fn set_right(self, p: Option<Ptr<V, S>>) { unsafe { (*self.raw()).right = p } }
+
+        /// # Safety
+        /// 同 left
+        pub unsafe fn set_parent(self, p: Option<Ptr<V, S>>) { unsafe { (*self.raw()).parent = p } }
+
+        /// # Safety
+        /// 同 left
+        pub unsafe fn set_height(self, h: i32) { unsafe { (*self.raw()).height = h } }
+
+        /// # Safety
+        /// 同 left
+        pub unsafe fn set_summary(self, summary: S::Item) { unsafe { (*self.raw()).summary = summary } }
+
+        /// # Safety
+        /// 同 left
+        pub unsafe fn cmp_key(self, key: &str) -> Ordering { unsafe { key.cmp((*self.raw()).key.as_str()) } }
+
+        /// # Safety
+        /// 同 left
+        pub unsafe fn key(self) -> String { unsafe { (*self.raw()).key.clone() } }
+
+        // 借出值的引用，生命周期由调用方绑定到树的借用上
+        /// # Safety
+        /// 节点在 'a 期间不会释放，且期间没有对该节点的可变引用
+        pub unsafe fn value<'a>(self) -> &'a V { unsafe { &(*self.raw()).value } }
+
+        /// # Safety
+        /// 节点在 'a 期间不会释放，且期间没有对该节点的其它引用
+        pub unsafe fn value_mut<'a>(self) -> &'a mut V { unsafe { &mut (*self.raw()).value } }
+
+        // 交换两个不同节点的键和值
+        /// # Safety
+        /// 两个节点都尚未释放，且调用期间没有其它对它们的引用
+        pub unsafe fn swap_payload(self, other: Ptr<V, S>) {
+            assert!(self != other);
+            // SAFETY: 由调用方保证；两个指针不同，两个可变引用不会重叠
+            unsafe {
+                let (a, b) = (self.raw(), other.raw());
+                ::std::mem::swap(&mut (*a).key, &mut (*b).key);
+                ::std::mem::swap(&mut (*a).value, &mut (*b).value);
+            }
+        }
+    }
+}
+
+// 下文 SAFETY 注释中的“树中的指针”指从 root 经 left/right 可达的指针，以及这些节点的 parent；
+// 它们都指向由本树 alloc 且尚未 free 的节点，树的方法借用 self 期间不会有别处持有节点的引用
+
+pub struct RawAvlTree<V, S: Summary<V> = ()> {
+    root: Option<Ptr<V, S>>,
+    len: usize,
+}
+
+// 以下自由函数的参数都是树中的指针
+fn _height<V, S: Summary<V>>(p: Option<Ptr<V, S>>) -> i32 { p.map_or(-1, |p| unsafe { p.height() }) }
+
+fn _summary<V, S: Summary<V>>(p: Option<Ptr<V, S>>) -> S::Item { p.map_or_else(S::identity, |p| unsafe { p.summary() }) }
+
+// 根据孩子重新计算高度与摘要
+fn _refresh<V, S: Summary<V>>(p: Ptr<V, S>) {
+    // SAFETY: p 与它的孩子都是树中的指针
+    unsafe {
+        p.set_height(1 + _height(p.left()).max(_height(p.right())));
+        p.set_summary(S::combine(&S::combine(&_summary(p.left()), &p.measure()), &_summary(p.right())));
+    }
+}
+
+fn _balance_factor<V, S: Summary<V>>(p: Ptr<V, S>) -> i32 { unsafe { _height(p.left()) - _height(p.right()) } }
+
+fn _leftmost<V, S: Summary<V>>(mut p: Ptr<V, S>) -> Ptr<V, S> {
+    while let Some(l) = unsafe { p.left() } {
+        p = l;
+    }
+    p
+}
+
+fn _rightmost<V, S: Summary<V>>(mut p: Ptr<V, S>) -> Ptr<V, S> {
+    while let Some(r) = unsafe { p.right() } {
+        p = r;
+    }
+    p
+}
+
+fn _fold<V, S: Summary<V>>(node: Option<Ptr<V, S>>, lo: Bound<&String>, hi: Bound<&String>) -> S::Item {
+    let p = match node {
+        None => return S::identity(),
+        Some(p) => p,
+    };
+    if let (Bound::Unbounded, Bound::Unbounded) = (lo, hi) {
+        return _summary(node);
+    }
+    // SAFETY: p 是树中的指针
+    unsafe {
+        let below = match lo {
+            Bound::Included(k) => p.cmp_key(k) == Ordering::Greater,
+            Bound::Excluded(k) => p.cmp_key(k) != Ordering::Less,
+            Bound::Unbounded => false,
+        };
+        let above = match hi {
+            Bound::Included(k) => p.cmp_key(k) == Ordering::Less,
+            Bound::Excluded(k) => p.cmp_key(k) != Ordering::Greater,
+            Bound::Unbounded => false,
+        };
+        if below {
+            return _fold(p.right(), lo, hi);
+        }
+        if above {
+            return _fold(p.left(), lo, hi);
+        }
+        // 当前节点在区间内：左边只受下界约束，右边只受上界约束
+        let left = _fold(p.left(), lo, Bound::Unbounded);
+        let right = _fold(p.right(), Bound::Unbounded, hi);
+        S::combine(&S::combine(&left, &p.measure()), &right)
+    }
+}
+
+impl<V, S: Summary<V>> Default for RawAvlTree<V, S> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<V, S: Summary<V>> RawAvlTree<V, S> {
+    pub fn new() -> Self { Self { root: None, len: 0 } }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    pub fn height(&self) -> i32 { _height(self.root) }
+
+    pub fn depth(&self) -> i32 { self.height() }
+
+    // 整棵树的摘要
+    pub fn summary(&self) -> S::Item { _summary(self.root) }
+
+    // 键在 range 内的所有值的摘要，O(log n)
+    pub fn fold_range<R: RangeBounds<String>>(&self, range: R) -> S::Item {
+        _fold(self.root, range.start_bound(), range.end_bound())
+    }
+
+    // 与 AVLTree 相同，相等的键放左边
+    pub fn add(&mut self, key: String, value: V) {
+        let mut parent = None;
+        let mut cur = self.root;
+        let mut go_left = false;
+        // SAFETY: cur 与 parent 是树中的指针，node 刚分配
+        unsafe {
+            while let Some(p) = cur {
+                parent = Some(p);
+                go_left = p.cmp_key(&key) != Ordering::Greater;
+                cur = if go_left { p.left() } else { p.right() };
+            }
+            let node = Ptr::alloc(key, value);
+            node.set_parent(parent);
+            match parent {
+                None => self.root = Some(node),
+                Some(p) if go_left => p.set_left(Some(node)),
+                Some(p) => p.set_right(Some(node)),
+            }
+        }
+        self.len += 1;
+        self._rebalance(parent);
+    }
+
+    // 借用期间树不可修改，引用不会悬垂
+    #[allow(clippy::ptr_arg)]
+    pub fn get(&self, key: &String) -> Option<&V> {
+        // SAFETY: _find 返回树中的指针，引用的生命周期绑定在 &self 上
+        self._find(key).map(|p| unsafe { p.value() })
+    }
+
+    // 可写守卫，释放时刷新摘要
+    #[allow(clippy::ptr_arg)]
+    pub fn get_mut(&mut self, key: &String) -> Option<ValueMut<'_, V, S>> {
+        let p = self._find(key)?;
+        Some(ValueMut { tree: self, p })
+    }
+
+    #[allow(clippy::ptr_arg)]
+    pub fn update(&mut self, key: &String, value: V) -> Result<(), String> {
+        match self.get_mut(key) {
+            None => Err(format!("node={} not exists", key)),
+            Some(mut v) => {
+                *v = value;
+                Ok(())
+            }
+        }
+    }
+
+    // 在原处借用值，不克隆
+    #[allow(clippy::ptr_arg)]
+    pub fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &String, f: F) -> Option<R> { self.get(key).map(f) }
+
+    // 修改后沿父指针刷新摘要
+    #[allow(clippy::ptr_arg)]
+    pub fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, key: &String, f: F) -> Option<R> {
+        self.get_mut(key).map(|mut v| f(&mut v))
+    }
+
+    #[allow(clippy::ptr_arg)]
+    pub fn remove(&mut self, key: &String) -> Option<V> {
+        let mut x = self._find(key)?;
+        // SAFETY: x、succ、child、parent 都是树中的指针；x 从树中摘下之后才 free，此后不再使用
+        unsafe {
+            // 两个孩子时与后继交换内容，转化为删除至多一个孩子的节点
+            if let (Some(_), Some(r)) = (x.left(), x.right()) {
+                let succ = _leftmost(r);
+                x.swap_payload(succ);
+                x = succ;
+            }
+            let child = x.left().or(x.right());
+            let parent = x.parent();
+            if let Some(c) = child {
+                c.set_parent(parent);
+            }
+            self._replace_child(parent, x, child);
+            self.len -= 1;
+            self._rebalance(parent);
+            Some(x.free().value)
+        }
+    }
+
+    // SAFETY: root 是树中的指针，引用的生命周期绑定在 &self 上
+    pub fn min_val(&self) -> Option<&V> { self.root.map(|r| unsafe { _leftmost(r).value() }) }
+
+    pub fn max_val(&self) -> Option<&V> { self.root.map(|r| unsafe { _rightmost(r).value() }) }
+
+    // 中序的所有键
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys = Vec::with_capacity(self.len);
+        let mut stack = Vec::new();
+        let mut cur = self.root;
+        // SAFETY: 只沿树中的指针遍历
+        unsafe {
+            loop {
+                while let Some(p) = cur {
+                    stack.push(p);
+                    cur = p.left();
+                }
+                match stack.pop() {
+                    None => break,
+                    Some(p) => {
+                        keys.push(p.key());
+                        cur = p.right();
+                    }
+                }
+            }
+        }
+        keys
+    }
+
+    fn _find(&self, key: &str) -> Option<Ptr<V, S>> {
+        let mut cur = self.root;
+        while let Some(p) = cur {
+            // SAFETY: 只沿树中的指针查找
+            cur = unsafe {
+                match p.cmp_key(key) {
+                    Ordering::Less => p.left(),
+                    Ordering::Greater => p.right(),
+                    Ordering::Equal => return Some(p),
+                }
+            };
+        }
+        None
+    }
+
+    fn _replace_child(&mut self, parent: Option<Ptr<V, S>>, old: Ptr<V, S>, new: Option<Ptr<V, S>>) {
+        // SAFETY: parent 是树中的指针
+        unsafe {
+            match parent {
+                None => self.root = new,
+                Some(p) if p.left() == Some(old) => p.set_left(new),
+                Some(p) => p.set_right(new),
+            }
+        }
+    }
+
+    fn _rotate_right(&mut self, x: Ptr<V, S>) -> Ptr<V, S> {
+        // SAFETY: x 与它的左孩子、左孩子的右孩子、父节点都是树中的指针
+        unsafe {
+            let l = x.left().unwrap();
+            let lr = l.right();
+            x.set_left(lr);
+            if let Some(lr) = lr {
+                lr.set_parent(Some(x));
+            }
+            let parent = x.parent();
+            l.set_parent(parent);
+            self._replace_child(parent, x, Some(l));
+            l.set_right(Some(x));
+            x.set_parent(Some(l));
+            _refresh(x);
+            _refresh(l);
+            l
+        }
+    }
+
+    fn _rotate_left(&mut self, x: Ptr<V, S>) -> Ptr<V, S> {
+        // SAFETY: x 与它的右孩子、右孩子的左孩子、父节点都是树中的指针
+        unsafe {
+            let r = x.right().unwrap();
+            let rl = r.left();
+            x.set_right(rl);
+            if let Some(rl) = rl {
+                rl.set_parent(Some(x));
+            }
+            let parent = x.parent();
+            r.set_parent(parent);
+            self._replace_child(parent, x, Some(r));
+            r.set_left(Some(x));
+            x.set_parent(Some(r));
+            _refresh(x);
+            _refresh(r);
+            r
+        }
+    }
+
+    // 从 start 沿父指针向上更新高度与摘要，失衡时旋转
+    fn _rebalance(&mut self, start: Option<Ptr<V, S>>) {
+        let mut cur = start;
+        while let Some(mut p) = cur {
+            _refresh(p);
+            let bf = _balance_factor(p);
+            // SAFETY: p 与它的孩子都是树中的指针
+            unsafe {
+                if bf > 1 {
+                    let l = p.left().unwrap();
+                    if _balance_factor(l) < 0 {
+                        self._rotate_left(l);
+                    }
+                    p = self._rotate_right(p);
+                } else if bf < -1 {
+                    let r = p.right().unwrap();
+                    if _balance_factor(r) > 0 {
+                        self._rotate_right(r);
+                    }
+                    p = self._rotate_left(p);
+                }
+                cur = p.parent();
+            }
+        }
+    }
+}
+
+impl<V: Clone, S: Summary<V>> RawAvlTree<V, S> {
+    #[allow(clippy::ptr_arg)]
+    pub fn find_and_clone(&self, key: &String) -> Option<V> { self.get(key).cloned() }
+
+    pub fn min_val_clone(&self) -> Option<V> { self.min_val().cloned() }
+
+    pub fn max_val_clone(&self) -> Option<V> { self.max_val().cloned() }
+}
+
+// get_mut 返回的守卫，独占借用整棵树，释放时沿父指针刷新摘要
+pub struct ValueMut<'a, V: 'a, S: Summary<V> + 'a> {
+    tree: &'a mut RawAvlTree<V, S>,
+    p: Ptr<V, S>,
+}
+
+impl<'a, V, S: Summary<V>> Deref for ValueMut<'a, V, S> {
+    type Target = V;
+
+    // SAFETY: p 是树中的指针，守卫独占借用树
+    fn deref(&self) -> &V { unsafe { self.p.value() } }
+}
+
+impl<'a, V, S: Summary<V>> DerefMut for ValueMut<'a, V, S> {
+    // SAFETY: 同上，&mut self 保证没有其它引用
+    fn deref_mut(&mut self) -> &mut V { unsafe { self.p.value_mut() } }
+}
+
+impl<'a, V, S: Summary<V>> Drop for ValueMut<'a, V, S> {
+    fn drop(&mut self) { self.tree._rebalance(Some(self.p)) }
+}
+
+impl<V: Clone, S: Summary<V>> Lookup<V> for RawAvlTree<V, S> {
+    fn add(&mut self, key: String, value: V) -> Option<V> {
+        if self._find(&key).is_some() {
+            return RawAvlTree::with_value_mut(self, &key, |v| mem::replace(v, value));
+        }
+        RawAvlTree::add(self, key, value);
+        None
+    }
+
+    fn remove(&mut self, key: &String) -> Option<V> { RawAvlTree::remove(self, key) }
+
+    fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &String, f: F) -> Option<R> {
+        RawAvlTree::with_value(self, key, f)
+    }
+
+    fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, key: &String, f: F) -> Option<R> {
+        RawAvlTree::with_value_mut(self, key, f)
+    }
+}
+
+impl<V, S: Summary<V>> Drop for RawAvlTree<V, S> {
+    fn drop(&mut self) {
+        let mut stack: Vec<Ptr<V, S>> = self.root.take().into_iter().collect();
+        while let Some(p) = stack.pop() {
+            // SAFETY: 每个节点恰好从父节点（或 root）入栈一次，释放后只用到取出的孩子
+            let node: RawNode<V, S> = unsafe { p.free() };
+            stack.extend(node.left);
+            stack.extend(node.right);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aggregate::Sum;
+    use testing::Lcg;
+    use tree::avl::AVLTree;
+
+    // Miri 下运行很慢，缩小规模
+    const ROUNDS: usize = if cfg!(miri) { 300 } else { 5000 };
+
+    // 校验父指针、有序、平衡与高度缓存
+    fn check<V, S: Summary<V>>(p: Option<Ptr<V, S>>, parent: Option<Ptr<V, S>>) -> i32 {
+        let p = match p {
+            None => return -1,
+            Some(p) => p,
+        };
+        // SAFETY: 测试只在树存活期间检查树中的指针
+        unsafe {
+            assert!(p.parent() == parent);
+            if let Some(l) = p.left() {
+                assert!(l.key() <= p.key());
+            }
+            if let Some(r) = p.right() {
+                assert!(r.key() >= p.key());
+            }
+            let lh = check(p.left(), Some(p));
+            let rh = check(p.right(), Some(p));
+            assert!((lh - rh).abs() <= 1);
+            assert_eq!(1 + lh.max(rh), p.height());
+            p.height()
+        }
+    }
+
+    #[test]
+    fn add_find_remove() {
+        let mut tree: RawAvlTree<i32> = RawAvlTree::new();
+        for i in 0..64 {
+            tree.add(format!("{:02}", i), i);
+        }
+        check(tree.root, None);
+        assert_eq!(6, tree.height());
+        assert_eq!(Some(10), tree.find_and_clone(&String::from("10")));
+        assert!(tree.update(&String::from("64"), 0).is_err());
+        assert!(tree.update(&String::from("11"), 110).is_ok());
+        assert_eq!(Some(&110), tree.get(&String::from("11")));
+        *tree.get_mut(&String::from("13")).unwrap() += 100;
+        assert_eq!(Some(113), tree.find_and_clone(&String::from("13")));
+        assert_eq!(Some(0), tree.min_val_clone());
+        assert_eq!(Some(63), tree.max_val_clone());
+
+        for i in 0..32 {
+            assert_eq!(Some(i * 2), tree.remove(&format!("{:02}", i * 2)));
+            check(tree.root, None);
+        }
+        assert!(tree.remove(&String::from("00")).is_none());
+        assert_eq!(32, tree.len());
+        assert_eq!(Some(1), tree.min_val_clone());
+
+        // 重复的键都能保存，并能逐个删除
+        tree.add(String::from("dup"), 1);
+        tree.add(String::from("dup"), 2);
+        check(tree.root, None);
+        assert!(tree.remove(&String::from("dup")).is_some());
+        assert!(tree.remove(&String::from("dup")).is_some());
+        assert!(tree.remove(&String::from("dup")).is_none());
+    }
+
+    // 与安全版本 AVLTree 做差分测试
+    #[test]
+    fn differential_against_avl_tree() {
+        let mut raw: RawAvlTree<u32, Sum> = RawAvlTree::new();
+        let mut avl: AVLTree<u32, Sum> = AVLTree::new();
+        let mut rng = Lcg::new(31);
+        for _ in 0..ROUNDS {
+            let seed = rng.next_u32();
+            let key = format!("{}", (seed >> 16) % 300);
+            match (seed >> 8) & 3 {
+                0 => assert_eq!(avl.remove(&key), raw.remove(&key)),
+                1 => assert_eq!(avl.update(&key, seed >> 8).is_ok(), raw.update(&key, seed >> 8).is_ok()),
+                _ => {
+                    if !avl.contains_key(&key) {
+                        avl.add(key.clone(), seed >> 8);
+                        raw.add(key, seed >> 8);
+                    }
+                }
+            }
+        }
+        check(raw.root, None);
+        assert_eq!(avl.min_val_clone(), raw.min_val_clone());
+        assert_eq!(avl.max_val_clone(), raw.max_val_clone());
+        for key in raw.keys() {
+            assert_eq!(avl.find_and_clone(&key), raw.find_and_clone(&key));
+        }
+        assert_eq!(raw.len(), raw.keys().len());
+
+        // 摘要随插入、删除、修改和旋转保持一致
+        assert_eq!(avl.summary(), raw.summary());
+        for &(lo, hi) in [("1", "2"), ("150", "299"), ("3", "30"), ("", "9")].iter() {
+            let (lo, hi) = (String::from(lo), String::from(hi));
+            assert_eq!(avl.fold_range(lo.clone()..hi.clone()), raw.fold_range(lo.clone()..hi.clone()));
+            assert_eq!(avl.fold_range(lo.clone()..=hi.clone()), raw.fold_range(lo..=hi));
+        }
+        let key = raw.keys()[0].clone();
+        let before = raw.summary();
+        assert_eq!(Some(()), raw.with_value_mut(&key, |v| *v += 7));
+        assert_eq!(before + 7, raw.summary());
+    }
+}
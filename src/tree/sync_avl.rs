@@ -0,0 +1,509 @@
+// 线程安全的 AVL 树：结构与 avl::AVLTree 相同，只是把 Rc/RefCell/Weak 换成 Arc/RwLock/sync::Weak
+// 树本身是 Send + Sync 的，多线程共享时放进 Arc<RwLock<SyncAvlTree<V>>>，读操作可以并发
+// 节点上的锁只是为了在共享的 Arc 里改写字段：值和结构都只在 &mut self 下修改，这些锁不会发生争用；
+// 值直接存在节点里，不交出句柄，读用 with_value，改用 with_value_mut 并顺带刷新摘要
+// 与 AVLTree 相比支持 Summary、fold_range、with_value 与 Lookup，但没有游标、memory_usage 和 audit
+use std::cmp::Ordering;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+use std::sync::{Arc, RwLock, Weak};
+
+use tree::binary::Summary;
+use tree::search;
+
+pub type NodeArc<V, S = ()> = Arc<RwLock<SyncNode<V, S>>>;
+
+#[derive(Getters, Debug)]
+pub struct SyncNode<V, S: Summary<V> = ()> {
+    #[get = "pub"]
+    key: String,
+
+    #[get = "pub"]
+    value: V,
+
+    top: Option<Weak<RwLock<SyncNode<V, S>>>>,
+    left: Option<NodeArc<V, S>>,
+    right: Option<NodeArc<V, S>>,
+
+    // 叶子为 0，与 BinaryNode::height 一致
+    #[get = "pub"]
+    height: i32,
+
+    // 由 _refresh 维护，外部只读
+    #[get = "pub"]
+    summary: S::Item,
+}
+
+impl<V, S: Summary<V>> SyncNode<V, S> {
+    pub fn new(key: String, value: V) -> Self {
+        let summary = S::measure(&value);
+        Self { key, value, top: None, left: None, right: None, height: 0, summary }
+    }
+}
+
+// 以下辅助函数每次只短暂持有一个节点的锁，避免同一线程重复加锁造成死锁
+fn _left<V, S: Summary<V>>(n: &NodeArc<V, S>) -> Option<NodeArc<V, S>> { n.read().unwrap().left.clone() }
+
+fn _right<V, S: Summary<V>>(n: &NodeArc<V, S>) -> Option<NodeArc<V, S>> { n.read().unwrap().right.clone() }
+
+fn _top<V, S: Summary<V>>(n: &NodeArc<V, S>) -> Option<NodeArc<V, S>> {
+    n.read().unwrap().top.as_ref().and_then(|w| w.upgrade())
+}
+
+fn _set_left<V, S: Summary<V>>(n: &NodeArc<V, S>, c: Option<NodeArc<V, S>>) { n.write().unwrap().left = c }
+
+fn _set_right<V, S: Summary<V>>(n: &NodeArc<V, S>, c: Option<NodeArc<V, S>>) { n.write().unwrap().right = c }
+
+fn _set_top<V, S: Summary<V>>(n: &NodeArc<V, S>, p: Option<&NodeArc<V, S>>) {
+    n.write().unwrap().top = p.map(Arc::downgrade)
+}
+
+fn _height<V, S: Summary<V>>(n: Option<&NodeArc<V, S>>) -> i32 { n.map_or(-1, |n| n.read().unwrap().height) }
+
+fn _summary<V, S: Summary<V>>(n: Option<&NodeArc<V, S>>) -> S::Item {
+    n.map_or_else(S::identity, |n| n.read().unwrap().summary.clone())
+}
+
+fn _measure<V, S: Summary<V>>(n: &NodeArc<V, S>) -> S::Item { S::measure(&n.read().unwrap().value) }
+
+// 取出已摘下节点中的值，此时树里已没有指向它的强引用
+fn _into_value<V, S: Summary<V>>(n: NodeArc<V, S>) -> V {
+    match Arc::try_unwrap(n) {
+        Ok(lock) => lock.into_inner().unwrap().value,
+        Err(_) => panic!("removed node is still shared"),
+    }
+}
+
+// 根据孩子重新计算高度与摘要
+fn _refresh<V, S: Summary<V>>(n: &NodeArc<V, S>) {
+    let (l, r) = (_left(n), _right(n));
+    let h = 1 + _height(l.as_ref()).max(_height(r.as_ref()));
+    let summary = S::combine(&S::combine(&_summary(l.as_ref()), &_measure(n)), &_summary(r.as_ref()));
+    let mut b = n.write().unwrap();
+    b.height = h;
+    b.summary = summary;
+}
+
+// 从 n 开始沿 top 向上刷新到根
+fn _refresh_up<V, S: Summary<V>>(n: NodeArc<V, S>) {
+    let mut cur = Some(n);
+    while let Some(n) = cur {
+        _refresh(&n);
+        cur = _top(&n);
+    }
+}
+
+fn _balance_factor<V, S: Summary<V>>(n: &NodeArc<V, S>) -> i32 {
+    _height(_left(n).as_ref()) - _height(_right(n).as_ref())
+}
+
+fn _leftmost<V, S: Summary<V>>(n: &NodeArc<V, S>) -> NodeArc<V, S> {
+    let mut cur = Arc::clone(n);
+    while let Some(l) = _left(&cur) {
+        cur = l;
+    }
+    cur
+}
+
+fn _rightmost<V, S: Summary<V>>(n: &NodeArc<V, S>) -> NodeArc<V, S> {
+    let mut cur = Arc::clone(n);
+    while let Some(r) = _right(&cur) {
+        cur = r;
+    }
+    cur
+}
+
+// 键落在区间内的节点摘要之和，与 search::fold_range 相同
+fn _fold<V, S: Summary<V>>(node: Option<&NodeArc<V, S>>, lo: Bound<&String>, hi: Bound<&String>) -> S::Item {
+    let n = match node {
+        None => return S::identity(),
+        Some(n) => n,
+    };
+    if let (Bound::Unbounded, Bound::Unbounded) = (lo, hi) {
+        return _summary(node);
+    }
+    let key = n.read().unwrap().key.clone();
+    let below = match lo {
+        Bound::Included(k) => key < *k,
+        Bound::Excluded(k) => key <= *k,
+        Bound::Unbounded => false,
+    };
+    let above = match hi {
+        Bound::Included(k) => key > *k,
+        Bound::Excluded(k) => key >= *k,
+        Bound::Unbounded => false,
+    };
+    if below {
+        return _fold(_right(n).as_ref(), lo, hi);
+    }
+    if above {
+        return _fold(_left(n).as_ref(), lo, hi);
+    }
+    let left = _fold(_left(n).as_ref(), lo, Bound::Unbounded);
+    let right = _fold(_right(n).as_ref(), Bound::Unbounded, hi);
+    S::combine(&S::combine(&left, &_measure(n)), &right)
+}
+
+// 与 SearchTree 相同的接口，值只能在闭包内借用
+#[allow(clippy::ptr_arg)]
+pub trait SyncSearchTree<V: Clone, S: Summary<V> = ()> {
+    fn root(&self) -> &Option<NodeArc<V, S>>;
+    fn add_node(&mut self, node: NodeArc<V, S>);
+    // 摘下 key 对应的节点并返回，返回的节点不再与树相连
+    fn remove_node(&mut self, key: &String) -> Option<NodeArc<V, S>>;
+
+    // 以下为默认实现
+    fn find_node(&self, key: &String) -> Option<NodeArc<V, S>> {
+        let mut cur = self.root().clone();
+        while let Some(n) = cur {
+            let ord = key.cmp(&n.read().unwrap().key);
+            cur = match ord {
+                Ordering::Less => _left(&n),
+                Ordering::Greater => _right(&n),
+                Ordering::Equal => return Some(n),
+            };
+        }
+        None
+    }
+
+    fn find_and_clone(&self, key: &String) -> Option<V> { self.with_value(key, V::clone) }
+
+    fn add(&mut self, key: String, value: V) {
+        self.add_node(Arc::new(RwLock::new(SyncNode::new(key, value))));
+    }
+
+    fn update(&mut self, key: &String, value: V) -> Result<(), String> {
+        match self.find_node(key) {
+            None => Err(format!("node={} not exists", key)),
+            Some(n) => {
+                n.write().unwrap().value = value;
+                _refresh_up(n);
+                Ok(())
+            }
+        }
+    }
+
+    // 在节点的读锁内访问，不克隆
+    fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &String, f: F) -> Option<R> {
+        self.find_node(key).map(|n| f(&n.read().unwrap().value))
+    }
+
+    // 修改后沿 top 刷新摘要
+    fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, key: &String, f: F) -> Option<R> {
+        let n = self.find_node(key)?;
+        let r = f(&mut n.write().unwrap().value);
+        _refresh_up(n);
+        Some(r)
+    }
+
+    fn height(&self) -> i32 { _height(self.root().as_ref()) }
+
+    fn depth(&self) -> i32 { self.height() }
+
+    fn remove(&mut self, key: &String) -> Option<V> { self.remove_node(key).map(_into_value) }
+}
+
+pub struct SyncAvlTree<V, S: Summary<V> = ()> {
+    root: Option<NodeArc<V, S>>,
+}
+
+impl<V, S: Summary<V>> Default for SyncAvlTree<V, S> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<V: Clone, S: Summary<V>> SyncSearchTree<V, S> for SyncAvlTree<V, S> {
+    fn root(&self) -> &Option<NodeArc<V, S>> { &self.root }
+    // 相等的键放左边
+    fn add_node(&mut self, node: NodeArc<V, S>) {
+        let key = node.read().unwrap().key.clone();
+        let mut parent = None;
+        let mut go_left = false;
+        let mut cur = self.root.clone();
+        while let Some(n) = cur {
+            go_left = key <= n.read().unwrap().key;
+            cur = if go_left { _left(&n) } else { _right(&n) };
+            parent = Some(n);
+        }
+        _set_top(&node, parent.as_ref());
+        match parent {
+            None => self.root = Some(node),
+            Some(ref p) if go_left => _set_left(p, Some(node)),
+            Some(ref p) => _set_right(p, Some(node)),
+        }
+        self._rebalance(parent);
+    }
+
+    fn remove_node(&mut self, key: &String) -> Option<NodeArc<V, S>> {
+        let x = self.find_node(key)?;
+        let (left, right, parent) = (_left(&x), _right(&x), _top(&x));
+        let start = match (left, right) {
+            (Some(l), Some(r)) => {
+                // 后继节点顶替 x 的位置
+                let s = _leftmost(&r);
+                let sp = _top(&s).unwrap();
+                let start = if Arc::ptr_eq(&sp, &x) {
+                    Arc::clone(&s)
+                } else {
+                    let sr = _right(&s);
+                    if let Some(ref sr) = sr {
+                        _set_top(sr, Some(&sp));
+                    }
+                    _set_left(&sp, sr);
+                    _set_right(&s, Some(Arc::clone(&r)));
+                    _set_top(&r, Some(&s));
+                    sp
+                };
+                _set_left(&s, Some(Arc::clone(&l)));
+                _set_top(&l, Some(&s));
+                _set_top(&s, parent.as_ref());
+                self._replace_child(parent.as_ref(), &x, Some(s));
+                Some(start)
+            }
+            (l, r) => {
+                let child = l.or(r);
+                if let Some(ref c) = child {
+                    _set_top(c, parent.as_ref());
+                }
+                self._replace_child(parent.as_ref(), &x, child);
+                parent
+            }
+        };
+        {
+            let mut b = x.write().unwrap();
+            b.left = None;
+            b.right = None;
+            b.top = None;
+            b.height = 0;
+        }
+        self._rebalance(start);
+        Some(x)
+    }
+}
+
+impl<V, S: Summary<V>> SyncAvlTree<V, S> {
+    pub fn new() -> Self { Self { root: None } }
+
+    // 整棵树的摘要
+    pub fn summary(&self) -> S::Item { _summary(self.root.as_ref()) }
+
+    // 键在 range 内的所有值的摘要，O(log n)
+    pub fn fold_range<R: RangeBounds<String>>(&self, range: R) -> S::Item {
+        _fold(self.root.as_ref(), range.start_bound(), range.end_bound())
+    }
+
+    fn _replace_child(&mut self, parent: Option<&NodeArc<V, S>>, old: &NodeArc<V, S>, new: Option<NodeArc<V, S>>) {
+        match parent {
+            None => self.root = new,
+            Some(p) => {
+                let is_left = _left(p).is_some_and(|l| Arc::ptr_eq(&l, old));
+                if is_left {
+                    _set_left(p, new);
+                } else {
+                    _set_right(p, new);
+                }
+            }
+        }
+    }
+
+    fn _rotate_right(&mut self, x: &NodeArc<V, S>) -> NodeArc<V, S> {
+        let l = _left(x).unwrap();
+        let lr = _right(&l);
+        if let Some(ref lr) = lr {
+            _set_top(lr, Some(x));
+        }
+        _set_left(x, lr);
+        let parent = _top(x);
+        _set_top(&l, parent.as_ref());
+        self._replace_child(parent.as_ref(), x, Some(Arc::clone(&l)));
+        _set_right(&l, Some(Arc::clone(x)));
+        _set_top(x, Some(&l));
+        _refresh(x);
+        _refresh(&l);
+        l
+    }
+
+    fn _rotate_left(&mut self, x: &NodeArc<V, S>) -> NodeArc<V, S> {
+        let r = _right(x).unwrap();
+        let rl = _left(&r);
+        if let Some(ref rl) = rl {
+            _set_top(rl, Some(x));
+        }
+        _set_right(x, rl);
+        let parent = _top(x);
+        _set_top(&r, parent.as_ref());
+        self._replace_child(parent.as_ref(), x, Some(Arc::clone(&r)));
+        _set_left(&r, Some(Arc::clone(x)));
+        _set_top(x, Some(&r));
+        _refresh(x);
+        _refresh(&r);
+        r
+    }
+
+    // 从 start 沿 top 向上更新高度与摘要，失衡时旋转
+    fn _rebalance(&mut self, start: Option<NodeArc<V, S>>) {
+        let mut cur = start;
+        while let Some(mut n) = cur {
+            _refresh(&n);
+            let bf = _balance_factor(&n);
+            if bf > 1 {
+                let l = _left(&n).unwrap();
+                if _balance_factor(&l) < 0 {
+                    self._rotate_left(&l);
+                }
+                n = self._rotate_right(&n);
+            } else if bf < -1 {
+                let r = _right(&n).unwrap();
+                if _balance_factor(&r) > 0 {
+                    self._rotate_right(&r);
+                }
+                n = self._rotate_left(&n);
+            }
+            cur = _top(&n);
+        }
+    }
+}
+
+impl<V: Clone, S: Summary<V>> SyncAvlTree<V, S> {
+    pub fn min_val_clone(&self) -> Option<V> { self.root.as_ref().map(|r| _leftmost(r).read().unwrap().value.clone()) }
+
+    pub fn max_val_clone(&self) -> Option<V> { self.root.as_ref().map(|r| _rightmost(r).read().unwrap().value.clone()) }
+}
+
+impl<V: Clone, S: Summary<V>> search::Lookup<V> for SyncAvlTree<V, S> {
+    fn add(&mut self, key: String, value: V) -> Option<V> {
+        if self.find_node(&key).is_some() {
+            return SyncSearchTree::with_value_mut(self, &key, |v| mem::replace(v, value));
+        }
+        SyncSearchTree::add(self, key, value);
+        None
+    }
+
+    fn remove(&mut self, key: &String) -> Option<V> { SyncSearchTree::remove(self, key) }
+
+    fn with_value<R, F: FnOnce(&V) -> R>(&self, key: &String, f: F) -> Option<R> {
+        SyncSearchTree::with_value(self, key, f)
+    }
+
+    fn with_value_mut<R, F: FnOnce(&mut V) -> R>(&mut self, key: &String, f: F) -> Option<R> {
+        SyncSearchTree::with_value_mut(self, key, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aggregate::Sum;
+    use std::thread;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    // 校验 top 指针、有序、平衡与高度缓存
+    fn check<V>(n: Option<&NodeArc<V>>, top: Option<&NodeArc<V>>, keys: &mut Vec<String>) -> i32 {
+        let n = match n {
+            None => return -1,
+            Some(n) => n,
+        };
+        match (_top(n), top) {
+            (None, None) => (),
+            (Some(a), Some(b)) => assert!(Arc::ptr_eq(&a, b)),
+            _ => panic!("top mismatch"),
+        }
+        let lh = check(_left(n).as_ref(), Some(n), keys);
+        keys.push(n.read().unwrap().key.clone());
+        let rh = check(_right(n).as_ref(), Some(n), keys);
+        assert!((lh - rh).abs() <= 1);
+        assert_eq!(1 + lh.max(rh), *n.read().unwrap().height());
+        1 + lh.max(rh)
+    }
+
+    fn check_tree<V>(tree: &SyncAvlTree<V>) -> Vec<String> {
+        let mut keys = Vec::new();
+        check(tree.root.as_ref(), None, &mut keys);
+        assert!(keys.windows(2).all(|w| w[0] <= w[1]));
+        keys
+    }
+
+    #[test]
+    fn same_api_as_search_tree() {
+        assert_send_sync::<SyncAvlTree<String>>();
+
+        let mut tree = SyncAvlTree::new();
+        for i in 0..200 {
+            tree.add(format!("{:03}", i * 37 % 200), i);
+        }
+        assert_eq!(200, check_tree(&tree).len());
+        assert!(tree.height() <= 9);
+        assert_eq!(Some(1), tree.find_and_clone(&String::from("037")));
+        assert!(tree.update(&String::from("037"), 100).is_ok());
+        assert!(tree.update(&String::from("999"), 0).is_err());
+        assert_eq!(Some(()), tree.with_value_mut(&String::from("000"), |v| *v += 5));
+        assert_eq!(Some(5), tree.min_val_clone());
+
+        for i in (0..200).filter(|i| i % 3 != 0) {
+            assert!(tree.remove(&format!("{:03}", i)).is_some());
+            check_tree(&tree);
+        }
+        assert!(tree.remove(&String::from("001")).is_none());
+        assert_eq!(67, check_tree(&tree).len());
+        assert_eq!(Some(5), tree.remove(&String::from("000")));
+        assert_eq!(Some(54), tree.max_val_clone());
+
+        // 摘要随插入、删除、修改和旋转保持一致
+        let mut sum: SyncAvlTree<i64, Sum> = SyncAvlTree::new();
+        for i in 0..100 {
+            sum.add(format!("{:02}", i * 7 % 100), i);
+        }
+        for i in (0..100).filter(|i| i % 4 == 0) {
+            sum.remove(&format!("{:02}", i));
+        }
+        let value = |k: i64| (0..100).find(|i| i * 7 % 100 == k).unwrap();
+        let expect = |lo: i64, hi: i64| (lo..hi).filter(|k| k % 4 != 0).map(value).sum::<i64>();
+        assert_eq!(expect(0, 100), sum.summary());
+        assert_eq!(expect(10, 30), sum.fold_range(String::from("10")..String::from("30")));
+        assert_eq!(Some(()), sum.with_value_mut(&String::from("21"), |v| *v += 1000));
+        assert!(sum.update(&String::from("22"), 0).is_ok());
+        assert_eq!(expect(0, 100) + 1000 - value(22), sum.summary());
+        assert_eq!(Some(1003), sum.with_value(&String::from("21"), |v| *v));
+    }
+
+    #[test]
+    fn hammer_from_threads() {
+        let tree = Arc::new(RwLock::new(SyncAvlTree::new()));
+        tree.write().unwrap().add(String::from("counter"), 0u64);
+
+        let mut handles = Vec::new();
+        for t in 0..4u64 {
+            let writer = Arc::clone(&tree);
+            // 写线程：各自插入互不相交的键，再删掉其中一半
+            handles.push(thread::spawn(move || {
+                for i in 0..200 {
+                    writer.write().unwrap().add(format!("w{}-{:03}", t, i), t * 1000 + i);
+                }
+                for i in (0..200).filter(|i| i % 2 == 1) {
+                    assert!(writer.write().unwrap().remove(&format!("w{}-{:03}", t, i)).is_some());
+                }
+            }));
+            let reader = Arc::clone(&tree);
+            // 读线程：持有树的读锁并发查找，计数要在树的写锁下累加
+            handles.push(thread::spawn(move || {
+                for i in 0..500 {
+                    let seen = reader.read().unwrap().with_value(&format!("w{}-{:03}", t, i % 200), |v| *v);
+                    assert!(seen.is_none_or(|v| v == t * 1000 + i % 200));
+                    assert!(reader.write().unwrap().with_value_mut(&String::from("counter"), |v| *v += 1).is_some());
+                }
+            }));
+        }
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        let tree = tree.read().unwrap();
+        let keys = check_tree(&tree);
+        assert_eq!(4 * 100 + 1, keys.len());
+        assert_eq!(Some(2000), tree.find_and_clone(&String::from("counter")));
+        for t in 0..4u64 {
+            assert_eq!(Some(t * 1000 + 2), tree.find_and_clone(&format!("w{}-002", t)));
+            assert_eq!(None, tree.find_and_clone(&format!("w{}-003", t)));
+        }
+    }
+}
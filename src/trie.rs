@@ -0,0 +1,356 @@
+// 压缩前缀树（radix tree）：边上的标签按字节存放，子节点按标签首字节排序
+use std::mem;
+
+struct TrieNode<V> {
+    label: Vec<u8>,
+    value: Option<V>,
+    children: Vec<TrieNode<V>>,
+}
+
+pub struct RadixTrie<V> {
+    root: TrieNode<V>,
+    len: usize,
+}
+
+fn _common_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b.iter()).take_while(|&(x, y)| x == y).count()
+}
+
+// 完整的键必然是合法的 UTF-8；公共前缀可能停在字符中间，需要截到字符边界
+fn _bytes_to_string(mut bytes: Vec<u8>) -> String {
+    match String::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(e) => {
+            let valid = e.utf8_error().valid_up_to();
+            bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).unwrap()
+        }
+    }
+}
+
+impl<V> TrieNode<V> {
+    fn new(label: Vec<u8>, value: Option<V>) -> Self {
+        Self { label, value, children: Vec::new() }
+    }
+
+    fn child_index(&self, first: u8) -> Result<usize, usize> {
+        self.children.binary_search_by(|c| c.label[0].cmp(&first))
+    }
+
+    fn insert(&mut self, key: &[u8], value: V) -> Option<V> {
+        if key.is_empty() {
+            return self.value.replace(value);
+        }
+
+        let i = match self.child_index(key[0]) {
+            Ok(i) => i,
+            Err(i) => {
+                self.children.insert(i, TrieNode::new(key.to_vec(), Some(value)));
+                return None;
+            }
+        };
+
+        let child = &mut self.children[i];
+        let common = _common_len(&child.label, key);
+        if common == child.label.len() {
+            return child.insert(&key[common..], value);
+        }
+
+        // 拆分边：公共部分成为新的中间节点
+        let suffix = child.label.split_off(common);
+        let prefix = mem::replace(&mut child.label, suffix);
+        let old = mem::replace(child, TrieNode::new(prefix, None));
+        child.children.push(old);
+        if common == key.len() {
+            child.value = Some(value);
+        } else {
+            let leaf = TrieNode::new(key[common..].to_vec(), Some(value));
+            let pos = if leaf.label[0] < child.children[0].label[0] { 0 } else { 1 };
+            child.children.insert(pos, leaf);
+        }
+        None
+    }
+
+    fn find(&self, key: &[u8]) -> Option<&TrieNode<V>> {
+        if key.is_empty() {
+            return Some(self);
+        }
+        let child = &self.children[self.child_index(key[0]).ok()?];
+        if key.starts_with(&child.label) {
+            child.find(&key[child.label.len()..])
+        } else {
+            None
+        }
+    }
+
+    fn find_mut(&mut self, key: &[u8]) -> Option<&mut TrieNode<V>> {
+        if key.is_empty() {
+            return Some(self);
+        }
+        let i = self.child_index(key[0]).ok()?;
+        let child = &mut self.children[i];
+        if key.starts_with(&child.label) {
+            let len = child.label.len();
+            child.find_mut(&key[len..])
+        } else {
+            None
+        }
+    }
+
+    fn remove(&mut self, key: &[u8]) -> Option<V> {
+        if key.is_empty() {
+            return self.value.take();
+        }
+
+        let i = self.child_index(key[0]).ok()?;
+        let removed = {
+            let child = &mut self.children[i];
+            if !key.starts_with(&child.label) {
+                return None;
+            }
+            let len = child.label.len();
+            child.remove(&key[len..])
+        };
+
+        if removed.is_some() {
+            let child = &mut self.children[i];
+            if child.value.is_none() {
+                match child.children.len() {
+                    0 => {
+                        self.children.remove(i);
+                    }
+                    // 只剩一个孩子的空节点与孩子合并，保持压缩
+                    1 => {
+                        let mut only = child.children.pop().unwrap();
+                        let mut label = mem::take(&mut child.label);
+                        label.extend_from_slice(&only.label);
+                        only.label = label;
+                        *child = only;
+                    }
+                    _ => (),
+                }
+            }
+        }
+        removed
+    }
+}
+
+impl<V> Default for RadixTrie<V> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<V> RadixTrie<V> {
+    pub fn new() -> Self {
+        Self { root: TrieNode::new(Vec::new(), None), len: 0 }
+    }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    // 插入，key 已存在时替换并返回旧值
+    pub fn add(&mut self, key: String, value: V) -> Option<V> {
+        let old = self.root.insert(key.as_bytes(), value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn find(&self, key: &str) -> Option<&V> {
+        self.root.find(key.as_bytes()).and_then(|n| n.value.as_ref())
+    }
+
+    pub fn find_mut(&mut self, key: &str) -> Option<&mut V> {
+        self.root.find_mut(key.as_bytes()).and_then(|n| n.value.as_mut())
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool { self.find(key).is_some() }
+
+    pub fn update(&mut self, key: &str, value: V) -> Result<(), String> {
+        match self.find_mut(key) {
+            None => Err(format!("node={} not exists", key)),
+            Some(v) => {
+                *v = value;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let r = self.root.remove(key.as_bytes());
+        if r.is_some() {
+            self.len -= 1;
+        }
+        r
+    }
+
+    // 按字典序遍历所有以 prefix 开头的键值对
+    pub fn iter_prefix(&self, prefix: &str) -> Iter<'_, V> {
+        let mut path = Vec::new();
+        let mut rest = prefix.as_bytes();
+        let mut node = &self.root;
+        while !rest.is_empty() {
+            let child = match node.child_index(rest[0]) {
+                Ok(i) => &node.children[i],
+                Err(_) => return Iter { stack: Vec::new() },
+            };
+            let common = _common_len(&child.label, rest);
+            if common < rest.len() && common < child.label.len() {
+                return Iter { stack: Vec::new() };
+            }
+            // prefix 可能在这条边的中间结束，此时整棵子树都匹配
+            path.extend_from_slice(&child.label);
+            rest = &rest[common..];
+            node = child;
+        }
+        Iter { stack: vec![(path, node)] }
+    }
+
+    pub fn keys_with_prefix(&self, prefix: &str) -> Vec<String> {
+        self.iter_prefix(prefix).map(|(k, _)| k).collect()
+    }
+
+    // 所有键的最长公共前缀
+    pub fn longest_common_prefix(&self) -> String {
+        let mut path = Vec::new();
+        let mut node = &self.root;
+        while node.value.is_none() && node.children.len() == 1 {
+            node = &node.children[0];
+            path.extend_from_slice(&node.label);
+        }
+        _bytes_to_string(path)
+    }
+
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter { stack: vec![(Vec::new(), &self.root)] }
+    }
+}
+
+impl<V: Clone> RadixTrie<V> {
+    pub fn find_and_clone(&self, key: &str) -> Option<V> { self.find(key).cloned() }
+}
+
+// 先序遍历，子节点按首字节有序，因此输出按键的字典序
+pub struct Iter<'a, V: 'a> {
+    stack: Vec<(Vec<u8>, &'a TrieNode<V>)>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (path, node) = self.stack.pop()?;
+            for child in node.children.iter().rev() {
+                let mut p = path.clone();
+                p.extend_from_slice(&child.label);
+                self.stack.push((p, child));
+            }
+            if let Some(ref v) = node.value {
+                return Some((String::from_utf8(path).unwrap(), v));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use testing::Lcg;
+
+    // 除根节点外，不允许出现没有值且少于两个孩子的节点
+    fn check<V>(node: &TrieNode<V>, is_root: bool) {
+        if !is_root {
+            assert!(!node.label.is_empty());
+            assert!(node.value.is_some() || node.children.len() >= 2);
+        }
+        assert!(node.children.windows(2).all(|w| w[0].label[0] < w[1].label[0]));
+        for c in node.children.iter() {
+            check(c, false);
+        }
+    }
+
+    #[test]
+    fn add_find_remove() {
+        let mut trie = RadixTrie::new();
+        for key in ["user:42:name", "user:42:mail", "user:4", "user:7:name", "", "us"].iter() {
+            assert_eq!(None, trie.add(key.to_string(), key.len()));
+        }
+        check(&trie.root, true);
+        assert_eq!(6, trie.len());
+        assert_eq!(Some(&12), trie.find("user:42:name"));
+        assert_eq!(Some(&0), trie.find(""));
+        assert_eq!(None, trie.find("user:42"));
+        assert_eq!(None, trie.find("user:42:name:x"));
+        assert_eq!(Some(6), trie.add(String::from("user:4"), 60));
+        assert!(trie.update("user:42", 1).is_err());
+        assert!(trie.update("us", 20).is_ok());
+        assert_eq!(Some(20), trie.find_and_clone("us"));
+
+        assert_eq!(None, trie.remove("user:42"));
+        assert_eq!(Some(60), trie.remove("user:4"));
+        check(&trie.root, true);
+        assert_eq!(Some(12), trie.remove("user:42:name"));
+        check(&trie.root, true);
+        assert_eq!(Some(&12), trie.find("user:42:mail"));
+        assert_eq!(4, trie.len());
+    }
+
+    #[test]
+    fn prefix_queries() {
+        let mut trie = RadixTrie::new();
+        for key in ["user:42:name", "user:42:mail", "user:421", "user:7", "group:1"].iter() {
+            trie.add(key.to_string(), ());
+        }
+
+        assert_eq!(vec!["user:42:mail", "user:42:name"], trie.keys_with_prefix("user:42:"));
+        assert_eq!(vec!["user:421", "user:42:mail", "user:42:name"], trie.keys_with_prefix("user:4"));
+        assert_eq!(vec!["user:421", "user:42:mail", "user:42:name"], trie.keys_with_prefix("user:42"));
+        assert_eq!(vec!["user:7"], trie.keys_with_prefix("user:7"));
+        assert!(trie.keys_with_prefix("user:8").is_empty());
+        assert!(trie.keys_with_prefix("user:7:").is_empty());
+        assert_eq!(5, trie.keys_with_prefix("").len());
+
+        assert_eq!("", trie.longest_common_prefix());
+        trie.remove("group:1");
+        assert_eq!("user:", trie.longest_common_prefix());
+        trie.remove("user:7");
+        assert_eq!("user:42", trie.longest_common_prefix());
+        trie.add(String::from("user:4"), ());
+        assert_eq!("user:4", trie.longest_common_prefix());
+
+        let mut trie = RadixTrie::new();
+        trie.add(String::from("café"), 1);
+        trie.add(String::from("cafè"), 2);
+        assert_eq!("caf", trie.longest_common_prefix());
+        assert_eq!(vec!["café"], trie.keys_with_prefix("café"));
+    }
+
+    #[test]
+    fn random_against_btreemap() {
+        let mut trie = RadixTrie::new();
+        let mut model = BTreeMap::new();
+        let mut rng = Lcg::new(17);
+        for _ in 0..4000 {
+            let seed = rng.next_u32();
+            let key = format!("{:o}", (seed >> 10) % 2000);
+            if (seed >> 4) % 3 == 1 {
+                assert_eq!(model.remove(&key), trie.remove(&key));
+            } else {
+                assert_eq!(model.insert(key.clone(), seed), trie.add(key, seed));
+            }
+        }
+        check(&trie.root, true);
+        assert_eq!(model.len(), trie.len());
+
+        let expect: Vec<(String, &u32)> = model.iter().map(|(k, v)| (k.clone(), v)).collect();
+        let actual: Vec<(String, &u32)> = trie.iter().collect();
+        assert_eq!(expect, actual);
+
+        let expect: Vec<String> = model.keys().filter(|k| k.starts_with("17")).cloned().collect();
+        assert_eq!(expect, trie.keys_with_prefix("17"));
+    }
+}
@@ -0,0 +1,274 @@
+// 并查集：按秩合并 + 路径压缩；另有以 String 为键的版本
+use std::collections::HashMap;
+
+// 元素用 0..len 的下标表示
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    size: Vec<usize>,
+    // 同一集合的元素串成环，合并时交换两个环的 next 即可 O(1) 拼接
+    next: Vec<usize>,
+    sets: usize,
+}
+
+impl Default for DisjointSet {
+    fn default() -> Self { Self::new(0) }
+}
+
+impl DisjointSet {
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            rank: vec![0; len],
+            size: vec![1; len],
+            next: (0..len).collect(),
+            sets: len,
+        }
+    }
+
+    pub fn len(&self) -> usize { self.parent.len() }
+
+    pub fn is_empty(&self) -> bool { self.parent.is_empty() }
+
+    // 集合个数
+    pub fn set_count(&self) -> usize { self.sets }
+
+    // 新增一个单元素集合，返回它的下标
+    pub fn make_set(&mut self) -> usize {
+        let x = self.parent.len();
+        self.parent.push(x);
+        self.rank.push(0);
+        self.size.push(1);
+        self.next.push(x);
+        self.sets += 1;
+        x
+    }
+
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // 路径压缩：沿途节点直接指向根
+        let mut cur = x;
+        while self.parent[cur] != root {
+            let up = self.parent[cur];
+            self.parent[cur] = root;
+            cur = up;
+        }
+        root
+    }
+
+    // 合并 a、b 所在集合，原本已在同一集合时返回 false
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut ra, mut rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return false;
+        }
+        if self.rank[ra] < self.rank[rb] {
+            ::std::mem::swap(&mut ra, &mut rb);
+        }
+        self.parent[rb] = ra;
+        if self.rank[ra] == self.rank[rb] {
+            self.rank[ra] += 1;
+        }
+        self.size[ra] += self.size[rb];
+        self.next.swap(ra, rb);
+        self.sets -= 1;
+        true
+    }
+
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool { self.find(a) == self.find(b) }
+
+    pub fn set_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    // 与 x 同一集合的所有元素（含 x 本身），从 x 开始沿环遍历
+    pub fn members(&self, x: usize) -> Members<'_> {
+        Members { next: &self.next, start: x, cur: Some(x) }
+    }
+}
+
+pub struct Members<'a> {
+    next: &'a [usize],
+    start: usize,
+    cur: Option<usize>,
+}
+
+impl<'a> Iterator for Members<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let x = self.cur?;
+        let n = self.next[x];
+        self.cur = if n == self.start { None } else { Some(n) };
+        Some(x)
+    }
+}
+
+// 以 String 为键的并查集，键与 BinaryNode 的 key 类型一致
+#[derive(Default)]
+pub struct KeyedDisjointSet {
+    index: HashMap<String, usize>,
+    keys: Vec<String>,
+    sets: DisjointSet,
+}
+
+impl KeyedDisjointSet {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn len(&self) -> usize { self.keys.len() }
+
+    pub fn is_empty(&self) -> bool { self.keys.is_empty() }
+
+    pub fn set_count(&self) -> usize { self.sets.set_count() }
+
+    pub fn contains_key(&self, key: &str) -> bool { self.index.contains_key(key) }
+
+    // 新增单元素集合，key 已存在时返回 false
+    pub fn add(&mut self, key: String) -> bool {
+        if self.index.contains_key(&key) {
+            return false;
+        }
+        let x = self.sets.make_set();
+        self.index.insert(key.clone(), x);
+        self.keys.push(key);
+        true
+    }
+
+    fn _index(&self, key: &str) -> Result<usize, String> {
+        match self.index.get(key) {
+            None => Err(format!("node={} not exists", key)),
+            Some(&x) => Ok(x),
+        }
+    }
+
+    // 返回 key 所在集合的代表元
+    pub fn find(&mut self, key: &str) -> Option<&str> {
+        let x = *self.index.get(key)?;
+        let root = self.sets.find(x);
+        Some(&self.keys[root])
+    }
+
+    pub fn union(&mut self, a: &str, b: &str) -> Result<bool, String> {
+        let (x, y) = (self._index(a)?, self._index(b)?);
+        Ok(self.sets.union(x, y))
+    }
+
+    // 任一 key 不存在时返回 false
+    pub fn same_set(&mut self, a: &str, b: &str) -> bool {
+        match (self.index.get(a), self.index.get(b)) {
+            (Some(&x), Some(&y)) => self.sets.same_set(x, y),
+            _ => false,
+        }
+    }
+
+    pub fn set_size(&mut self, key: &str) -> Option<usize> {
+        let x = *self.index.get(key)?;
+        Some(self.sets.set_size(x))
+    }
+
+    // key 所在集合的所有成员，不存在时为空
+    pub fn members(&self, key: &str) -> Vec<&str> {
+        match self.index.get(key) {
+            None => Vec::new(),
+            Some(&x) => self.sets.members(x).map(|i| self.keys[i].as_str()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::Lcg;
+
+    #[test]
+    fn dense_union_find() {
+        let mut sets = DisjointSet::new(10);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+        assert!(sets.union(7, 8));
+        assert_eq!(6, sets.set_count());
+        assert!(sets.same_set(0, 3));
+        assert!(!sets.same_set(0, 7));
+        assert_eq!(4, sets.set_size(2));
+        assert_eq!(1, sets.set_size(9));
+
+        let mut m: Vec<usize> = sets.members(3).collect();
+        m.sort();
+        assert_eq!(vec![0, 1, 2, 3], m);
+        assert_eq!(vec![9], sets.members(9).collect::<Vec<_>>());
+
+        let x = sets.make_set();
+        sets.union(x, 9);
+        assert_eq!(11, sets.len());
+        assert_eq!(2, sets.members(x).count());
+    }
+
+    #[test]
+    fn random_against_labels() {
+        // 朴素做法：每个元素记录集合编号，合并时整体重标
+        let n = 200;
+        let mut sets = DisjointSet::new(n);
+        let mut label: Vec<usize> = (0..n).collect();
+        let mut rng = Lcg::new(3);
+        for _ in 0..300 {
+            let seed = rng.next_u32();
+            let a = (seed >> 16) as usize % n;
+            let seed = rng.next_u32();
+            let b = (seed >> 16) as usize % n;
+
+            let (la, lb) = (label[a], label[b]);
+            assert_eq!(la != lb, sets.union(a, b));
+            for l in label.iter_mut() {
+                if *l == lb {
+                    *l = la;
+                }
+            }
+        }
+        for a in 0..n {
+            let expect: Vec<usize> = (0..n).filter(|&b| label[b] == label[a]).collect();
+            let mut actual: Vec<usize> = sets.members(a).collect();
+            actual.sort();
+            assert_eq!(expect, actual);
+            assert_eq!(expect.len(), sets.set_size(a));
+        }
+        let mut labels = label.clone();
+        labels.sort();
+        labels.dedup();
+        assert_eq!(labels.len(), sets.set_count());
+    }
+
+    #[test]
+    fn keyed_union_find() {
+        let mut sets = KeyedDisjointSet::new();
+        for key in ["alice", "bob", "carol", "dave", "eve"].iter() {
+            assert!(sets.add(key.to_string()));
+        }
+        assert!(!sets.add(String::from("bob")));
+
+        assert_eq!(Ok(true), sets.union("alice", "bob"));
+        assert_eq!(Ok(true), sets.union("carol", "bob"));
+        assert_eq!(Ok(false), sets.union("alice", "carol"));
+        assert!(sets.union("alice", "mallory").is_err());
+
+        assert!(sets.same_set("carol", "alice"));
+        assert!(!sets.same_set("dave", "alice"));
+        assert!(!sets.same_set("dave", "mallory"));
+        assert_eq!(Some(3), sets.set_size("bob"));
+        assert_eq!(None, sets.set_size("mallory"));
+        assert_eq!(3, sets.set_count());
+
+        let root = sets.find("carol").unwrap().to_string();
+        assert_eq!(Some(root.as_str()), sets.find("alice"));
+        let mut m = sets.members("bob");
+        m.sort();
+        assert_eq!(vec!["alice", "bob", "carol"], m);
+        assert_eq!(vec!["eve"], sets.members("eve"));
+        assert!(sets.members("mallory").is_empty());
+    }
+}